@@ -0,0 +1,341 @@
+//! A command line tool for working with level files without opening a window.
+//!
+//! Run `cargo run --bin level_tool` to see what it can do.
+
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_jam_6::game::{
+    analysis::find_solutions,
+    board::{Board, LEGEND, render_level, render_simulation},
+    difficulty::estimate_difficulty,
+    generator::{Difficulty, generate_level},
+    level::{Level, LevelPlacement, level_files, read_level_file},
+    simulation::{DEFAULT_TIME_LIMIT_SECS, STEP_SECS, Simulation, simulate},
+    solver::{SolveResult, SolverLimits, solve},
+    text_level::{is_text_level, level_to_text},
+    validation::{Severity, validate_level},
+};
+
+const USAGE: &str = "\
+Usage: level_tool <command> [options]
+
+Commands:
+  validate <level>...   Check levels for mistakes, such as overlapping cells or
+                        reference solutions that don't work
+  simulate <level>      Run an experiment and print the outcome and the reactions
+  solve <level>...      Search for the solution that uses the fewest atoms
+  rate <level>...       Estimate how hard levels are, listed from easiest to hardest
+  generate <seed> <difficulty>
+                        Print a generated level as RON, where difficulty is easy,
+                        medium or hard
+  render <level>        Draw the level as text, with a legend
+  convert <level>       Print a `.ron` level as a text level, or a text level as RON
+
+Levels can be `.ron` or `.level.txt` files, or folders of them for `validate`,
+`solve` and `rate`.
+
+Options:
+  --placements <ron>    Atoms to place for `simulate` and `render`, written like a
+                        level's solutions, e.g. \"[(atom_type: Basic, position: (1, 0))]\"
+  --solution <n>        Place the atoms from the level's nth reference solution
+  --frames              Make `simulate` draw the experiment on every tick
+  --all                 Make `solve` find every solution, grouped by the reactions
+                        they cause
+  --max-atoms <n>       Most atoms for `solve` to try placing
+  --max-simulations <n> Most experiments for `solve` to run before giving up";
+
+fn main() -> AppExit {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Some((command, args)) = args.split_first() else {
+        println!("{USAGE}");
+        return AppExit::Success;
+    };
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}\n\n{USAGE}");
+            return AppExit::error();
+        }
+    };
+    let result = match command.as_str() {
+        "validate" => validate(&options),
+        "simulate" => run_simulation(&options),
+        "solve" => solve_levels(&options),
+        "rate" => rate(&options),
+        "generate" => generate(&options),
+        "render" => render(&options),
+        "convert" => convert(&options),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(true)
+        }
+        _ => Err(format!("Unknown command `{command}`\n\n{USAGE}")),
+    };
+    match result {
+        Ok(true) => AppExit::Success,
+        Ok(false) => AppExit::error(),
+        Err(error) => {
+            eprintln!("{error}");
+            AppExit::error()
+        }
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    /// Levels, and anything else given without an option, such as the seed for `generate`
+    paths: Vec<PathBuf>,
+    placements: Option<Vec<LevelPlacement>>,
+    /// Index of a reference solution, counted from 1
+    solution: Option<usize>,
+    frames: bool,
+    all: bool,
+    max_atoms: Option<usize>,
+    max_simulations: Option<usize>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = |name: &str| args.next().ok_or_else(|| format!("{name} needs a value"));
+            let number = |name: &str, value: &str| {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| format!("{name} should be a number, not `{value}`"))
+            };
+            match arg.as_str() {
+                "--placements" => {
+                    let value = value(arg)?;
+                    options.placements = Some(
+                        ron::from_str(value)
+                            .map_err(|error| format!("Couldn't read the placements: {error}"))?,
+                    );
+                }
+                "--solution" => options.solution = Some(number(arg, value(arg)?)?),
+                "--frames" => options.frames = true,
+                "--all" => options.all = true,
+                "--max-atoms" => options.max_atoms = Some(number(arg, value(arg)?)?),
+                "--max-simulations" => options.max_simulations = Some(number(arg, value(arg)?)?),
+                _ if arg.starts_with("--") => return Err(format!("Unknown option `{arg}`")),
+                _ => options.paths.push(PathBuf::from(arg)),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Every level file given, with folders replaced by the levels in them
+    fn level_paths(&self) -> Result<Vec<PathBuf>, String> {
+        if self.paths.is_empty() {
+            return Err("No levels given".to_string());
+        }
+        let mut paths = Vec::new();
+        for path in &self.paths {
+            if path.is_dir() {
+                paths.extend(
+                    level_files(path)
+                        .map_err(|error| format!("Couldn't read {}: {error}", path.display()))?,
+                );
+            } else {
+                paths.push(path.clone());
+            }
+        }
+        Ok(paths)
+    }
+
+    /// The only level file given
+    fn level(&self) -> Result<Level, String> {
+        let [path] = self.paths.as_slice() else {
+            return Err("Expected exactly one level".to_string());
+        };
+        read_level_file(path)
+            .map_err(|error| format!("{}: couldn't load level: {error}", path.display()))
+    }
+
+    /// The atoms to place in the level, if any
+    fn placements(&self, level: &Level) -> Result<Vec<LevelPlacement>, String> {
+        match (&self.placements, self.solution) {
+            (Some(_), Some(_)) => {
+                Err("Use either --placements or --solution, not both".to_string())
+            }
+            (Some(placements), None) => Ok(placements.clone()),
+            (None, Some(number)) => number
+                .checked_sub(1)
+                .and_then(|index| level.solutions.get(index))
+                .cloned()
+                .ok_or_else(|| {
+                    format!(
+                        "There's no solution {number}, the level has {}",
+                        level.solutions.len()
+                    )
+                }),
+            (None, None) => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Print every issue with the levels. Fails if any level has errors.
+fn validate(options: &Options) -> Result<bool, String> {
+    let mut valid = true;
+    for path in options.level_paths()? {
+        let name = path.display();
+        let level = match read_level_file(&path) {
+            Ok(level) => level,
+            Err(error) => {
+                println!("{name}: error: couldn't load level: {error}");
+                valid = false;
+                continue;
+            }
+        };
+        let issues = validate_level(&level);
+        if issues.is_empty() {
+            println!("{name}: ok");
+        }
+        for issue in issues {
+            println!("{name}: {issue}");
+            valid &= issue.severity != Severity::Error;
+        }
+    }
+    Ok(valid)
+}
+
+/// Print how an experiment plays out. Fails if it doesn't complete the level.
+fn run_simulation(options: &Options) -> Result<bool, String> {
+    let level = options.level()?;
+    let placements = options.placements(&level)?;
+    let layout = LevelPlacement::layout(&placements);
+    if options.frames {
+        print_frames(&level, &placements);
+    }
+    let report = simulate(&level, &layout);
+    println!(
+        "{:?} after {} ticks with {} reaction{}",
+        report.outcome,
+        report.ticks,
+        report.reactions.len(),
+        if report.reactions.len() == 1 { "" } else { "s" }
+    );
+    for reaction in &report.reactions {
+        println!("    {} (depth {})", reaction.describe(), reaction.depth);
+    }
+    Ok(report.is_complete())
+}
+
+/// Draw the experiment at the start and on every tick until it ends
+fn print_frames(level: &Level, placements: &[LevelPlacement]) {
+    let bounds = Board::for_level(level, placements).bounds();
+    let mut simulation = Simulation::new(level, &LevelPlacement::layout(placements));
+    println!("Tick 0\n{}", render_simulation(&simulation, bounds));
+    let mut time = 0.0;
+    loop {
+        let tick = simulation.tick();
+        let finished = simulation.step().is_some() || time >= DEFAULT_TIME_LIMIT_SECS;
+        time += STEP_SECS;
+        if finished || simulation.tick() != tick {
+            println!(
+                "Tick {}\n{}",
+                simulation.tick(),
+                render_simulation(&simulation, bounds)
+            );
+        }
+        if finished {
+            break;
+        }
+    }
+}
+
+/// Print the simplest solution to each level, as placements that can be given back to
+/// `simulate`, or every solution with `--all`. Fails if any level can't be solved.
+fn solve_levels(options: &Options) -> Result<bool, String> {
+    let mut all_solvable = true;
+    for path in options.level_paths()? {
+        let name = path.display();
+        let level = match read_level_file(&path) {
+            Ok(level) => level,
+            Err(error) => {
+                println!("{name}: couldn't load level: {error}");
+                all_solvable = false;
+                continue;
+            }
+        };
+        let mut limits = SolverLimits::for_level(&level);
+        limits.max_atoms = options.max_atoms.unwrap_or(limits.max_atoms);
+        limits.max_simulations = options.max_simulations.unwrap_or(limits.max_simulations);
+        if options.all {
+            let analysis = find_solutions(&level, &limits);
+            println!("{name}: {}", analysis.describe());
+            all_solvable &= !analysis.groups.is_empty();
+            continue;
+        }
+        let report = solve(&level, &limits);
+        println!("{name}: {}", report.describe(&limits));
+        match &report.result {
+            SolveResult::Solved { solution, .. } => {
+                let placements = ron::to_string(solution).map_err(|error| error.to_string())?;
+                println!("    {placements}");
+            }
+            // Running out of experiments doesn't mean the level can't be solved
+            SolveResult::GaveUp => {}
+            SolveResult::Unsolvable | SolveResult::NoGoal => all_solvable = false,
+        }
+    }
+    Ok(all_solvable)
+}
+
+/// Print the estimated difficulty of each level, from easiest to hardest. Levels without a
+/// reference solution are solved first, which can take a while.
+fn rate(options: &Options) -> Result<bool, String> {
+    let mut rated = Vec::new();
+    for path in options.level_paths()? {
+        let level = read_level_file(&path)
+            .map_err(|error| format!("{}: couldn't load level: {error}", path.display()))?;
+        rated.push((path, estimate_difficulty(&level)));
+    }
+    rated.sort_by(|(_, a), (_, b)| a.score().total_cmp(&b.score()));
+    for (path, estimate) in rated {
+        println!("{}: {}", path.display(), estimate.describe());
+    }
+    Ok(true)
+}
+
+/// Print a generated level as RON, ready to be saved as a level file
+fn generate(options: &Options) -> Result<bool, String> {
+    let [seed, difficulty] = options.paths.as_slice() else {
+        return Err("Expected a seed and a difficulty".to_string());
+    };
+    let (seed, difficulty) = (seed.to_string_lossy(), difficulty.to_string_lossy());
+    let seed = seed
+        .parse()
+        .map_err(|_| format!("Seed must be a whole number, not `{seed}`"))?;
+    let difficulty = Difficulty::from_name(&difficulty)
+        .ok_or_else(|| format!("Difficulty must be easy, medium or hard, not `{difficulty}`"))?;
+    let level = generate_level(seed, difficulty)
+        .ok_or_else(|| format!("Couldn't generate a level from seed {seed}"))?;
+    let ron = ron::ser::to_string_pretty(&level, ron::ser::PrettyConfig::default())
+        .map_err(|error| error.to_string())?;
+    println!("{ron}");
+    Ok(true)
+}
+
+fn render(options: &Options) -> Result<bool, String> {
+    let level = options.level()?;
+    let placements = options.placements(&level)?;
+    println!("{}", render_level(&level, &placements));
+    println!("{LEGEND}");
+    Ok(true)
+}
+
+/// Print the level in the other format, so that levels can be moved between the two
+fn convert(options: &Options) -> Result<bool, String> {
+    let level = options.level()?;
+    let converted = if options.paths.iter().all(|path| is_text_level(path)) {
+        ron::ser::to_string_pretty(&level, ron::ser::PrettyConfig::default())
+            .map_err(|error| error.to_string())?
+    } else {
+        level_to_text(&level).map_err(|error| error.to_string())?
+    };
+    println!("{converted}");
+    Ok(true)
+}
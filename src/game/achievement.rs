@@ -0,0 +1,278 @@
+//! Achievements, unlocked by reactions, collisions and completing levels. Definitions live in
+//! [`ACHIEVEMENTS`]; unlocks and running totals are kept in the save file.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{LEVELS, save::SaveData};
+
+use super::{
+    atom::AtomType,
+    collision::{CollisionEvent, ReactionEvent},
+    leaderboard::{PendingReplay, is_replaying},
+    level::CurrentLevel,
+    movement::Movement,
+    reaction::{Reactant, react},
+    score::{LevelScore, record_level_score},
+    state::GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<AchievementUnlocked>();
+    app.add_observer(track_collisions);
+    // Replays don't earn achievements, as the player didn't make the reactions themselves
+    app.add_systems(
+        Update,
+        track_reactions.run_if(in_state(GameState::Running).and(not(is_replaying))),
+    );
+    app.add_systems(
+        OnEnter(GameState::LevelComplete),
+        track_level_complete
+            .after(record_level_score)
+            .run_if(not(is_replaying)),
+    );
+    // Totals change every reaction, so only write them out once the experiment stops
+    app.add_systems(OnExit(GameState::Running), save_achievements);
+}
+
+#[derive(Debug)]
+pub struct Achievement {
+    /// Key used in the save file. Must never change once released.
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub condition: AchievementCondition,
+}
+
+#[derive(Debug)]
+pub enum AchievementCondition {
+    /// Produce this many atoms of a type from reactions, across every experiment
+    Produce { atom_type: AtomType, count: u32 },
+    /// Have this many atoms meet in a single collision
+    Collision { atoms: usize },
+    /// Reach this chain depth in one experiment
+    ChainDepth(u32),
+    /// Complete a campaign level, optionally placing no more than `max_atoms`
+    CompleteLevel {
+        level: usize,
+        max_atoms: Option<u32>,
+    },
+    /// Get three stars on any campaign level
+    ThreeStars,
+    /// Complete every campaign level
+    CompleteCampaign,
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        id: "first_split",
+        name: "Fission",
+        description: "Produce a Splitting atom",
+        condition: AchievementCondition::Produce {
+            atom_type: AtomType::Splitting,
+            count: 1,
+        },
+    },
+    Achievement {
+        id: "three_way",
+        name: "Three's a Crowd",
+        description: "Trigger a 3-way collision",
+        condition: AchievementCondition::Collision { atoms: 3 },
+    },
+    Achievement {
+        id: "four_way",
+        name: "Pile-up",
+        description: "Trigger a 4-way collision",
+        condition: AchievementCondition::Collision { atoms: 4 },
+    },
+    Achievement {
+        id: "chain_depth_6",
+        name: "Chain Reaction",
+        description: "Reach a chain depth of 6",
+        condition: AchievementCondition::ChainDepth(6),
+    },
+    Achievement {
+        id: "antimatter_100",
+        name: "Antimatter Factory",
+        description: "Produce 100 Antimatter in total",
+        condition: AchievementCondition::Produce {
+            atom_type: AtomType::Antimatter,
+            count: 100,
+        },
+    },
+    Achievement {
+        id: "level_6_efficient",
+        name: "Less is More",
+        description: "Solve level 6 with three atoms or fewer",
+        condition: AchievementCondition::CompleteLevel {
+            level: 5,
+            max_atoms: Some(3),
+        },
+    },
+    Achievement {
+        id: "three_stars",
+        name: "Star Pupil",
+        description: "Get three stars on a level",
+        condition: AchievementCondition::ThreeStars,
+    },
+    Achievement {
+        id: "campaign",
+        name: "Graduate",
+        description: "Complete every level",
+        condition: AchievementCondition::CompleteCampaign,
+    },
+];
+
+/// Saved achievement state
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AchievementProgress {
+    /// Ids of unlocked achievements
+    #[serde(default)]
+    pub unlocked: BTreeSet<String>,
+    /// Atoms produced by reactions in total, keyed by atom type
+    #[serde(default)]
+    pub produced: BTreeMap<String, u32>,
+}
+
+impl AchievementProgress {
+    pub fn is_unlocked(&self, achievement: &Achievement) -> bool {
+        self.unlocked.contains(achievement.id)
+    }
+
+    pub fn produced(&self, atom_type: AtomType) -> u32 {
+        self.produced
+            .get(&format!("{atom_type:?}"))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// How far along a counting achievement is, as (current, target)
+    pub fn counter(&self, achievement: &Achievement) -> Option<(u32, u32)> {
+        match achievement.condition {
+            AchievementCondition::Produce { atom_type, count } if count > 1 => {
+                Some((self.produced(atom_type).min(count), count))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Sent when an achievement is unlocked for the first time
+#[derive(Event, Debug, Clone, Copy)]
+pub struct AchievementUnlocked(pub &'static Achievement);
+
+/// Unlock every achievement that isn't already unlocked and whose condition holds
+fn unlock_where(
+    save_data: &mut SaveData,
+    unlocked_events: &mut EventWriter<AchievementUnlocked>,
+    condition_met: impl Fn(&AchievementCondition, &AchievementProgress) -> bool,
+) {
+    let progress = &mut save_data.achievements;
+    for achievement in ACHIEVEMENTS {
+        if !progress.is_unlocked(achievement) && condition_met(&achievement.condition, progress) {
+            progress.unlocked.insert(achievement.id.to_string());
+            unlocked_events.write(AchievementUnlocked(achievement));
+        }
+    }
+}
+
+/// Only moving atoms that react count towards a collision's size, so atoms passing through
+/// portals or hitting walls and stationary atoms don't inflate it
+fn track_collisions(
+    trigger: Trigger<CollisionEvent>,
+    atoms: Query<(&AtomType, Option<&Movement>)>,
+    mut save_data: ResMut<SaveData>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+    pending_replay: Res<PendingReplay>,
+) {
+    if pending_replay.is_playing() {
+        return;
+    }
+    let reactants: Vec<Reactant> = atoms
+        .iter_many(&trigger.event().entities)
+        .map(|(atom_type, movement)| (*atom_type, movement.map(|movement| movement.direction)))
+        .collect();
+    if reactants
+        .iter()
+        .any(|(atom_type, _)| matches!(atom_type, AtomType::Portal(_)))
+        || react(&reactants).is_none()
+    {
+        return;
+    }
+    let size = reactants
+        .iter()
+        .filter(|(_, direction)| direction.is_some())
+        .count();
+    unlock_where(
+        &mut save_data,
+        &mut unlocked_events,
+        |condition, _| matches!(condition, AchievementCondition::Collision { atoms } if size >= *atoms),
+    );
+}
+
+fn track_reactions(
+    mut reactions: EventReader<ReactionEvent>,
+    mut save_data: ResMut<SaveData>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+) {
+    if reactions.is_empty() {
+        return;
+    }
+    let mut max_depth = 0;
+    for reaction in reactions.read() {
+        max_depth = max_depth.max(reaction.depth);
+        for atom_type in &reaction.outputs {
+            *save_data
+                .achievements
+                .produced
+                .entry(format!("{atom_type:?}"))
+                .or_default() += 1;
+        }
+    }
+    unlock_where(
+        &mut save_data,
+        &mut unlocked_events,
+        |condition, progress| match *condition {
+            AchievementCondition::Produce { atom_type, count } => {
+                progress.produced(atom_type) >= count
+            }
+            AchievementCondition::ChainDepth(depth) => max_depth >= depth,
+            _ => false,
+        },
+    );
+}
+
+fn track_level_complete(
+    mut save_data: ResMut<SaveData>,
+    mut unlocked_events: EventWriter<AchievementUnlocked>,
+    current_level: Res<CurrentLevel>,
+    score: Res<LevelScore>,
+) {
+    let Some(index) = current_level.get_index() else {
+        return;
+    };
+    let campaign_complete = LEVELS.iter().all(|level_file| {
+        save_data
+            .level(level_file)
+            .is_some_and(|progress| progress.completed)
+    });
+    unlock_where(
+        &mut save_data,
+        &mut unlocked_events,
+        |condition, _| match *condition {
+            AchievementCondition::CompleteLevel { level, max_atoms } => {
+                level == index && max_atoms.is_none_or(|max| score.atoms_placed <= max)
+            }
+            AchievementCondition::ThreeStars => score.stars == 3,
+            AchievementCondition::CompleteCampaign => campaign_complete,
+            _ => false,
+        },
+    );
+    save_data.save();
+}
+
+fn save_achievements(save_data: Res<SaveData>) {
+    save_data.save();
+}
@@ -0,0 +1,288 @@
+//! Finds every way to complete a level with a few atoms, so that designers can spot solutions
+//! that skip the idea the level is meant to teach.
+//!
+//! Solutions are grouped by the reactions they cause, since two layouts that cause the same
+//! reactions in the same order are really the same solution moved around.
+
+use bevy::prelude::*;
+
+use super::{
+    atom::AtomType,
+    collision::ReactionEvent,
+    level::{Level, LevelPlacement, PlacedAtom},
+    simulation::simulate,
+    solver::{SearchControl, SolverLimits, search},
+};
+
+/// Solutions shown for each group in the description
+const EXAMPLES_PER_GROUP: usize = 1;
+
+/// A reaction with its position and timing ignored
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReactionKind {
+    pub inputs: Vec<AtomType>,
+    pub outputs: Vec<AtomType>,
+}
+
+impl ReactionKind {
+    pub fn of(reaction: &ReactionEvent) -> Self {
+        let mut inputs = reaction.inputs.clone();
+        let mut outputs = reaction.outputs.clone();
+        // Order doesn't matter, so "Splitting + Basic" is the same as "Basic + Splitting"
+        inputs.sort();
+        outputs.sort();
+        Self { inputs, outputs }
+    }
+
+    /// e.g. "Basic + Basic → Splitting"
+    pub fn describe(&self) -> String {
+        let join = |atoms: &[AtomType]| {
+            if atoms.is_empty() {
+                "nothing".to_string()
+            } else {
+                atoms
+                    .iter()
+                    .map(|atom_type| format!("{atom_type:?}"))
+                    .collect::<Vec<_>>()
+                    .join(" + ")
+            }
+        };
+        format!("{} → {}", join(&self.inputs), join(&self.outputs))
+    }
+}
+
+/// Solutions that cause the same sequence of reactions
+#[derive(Debug, Clone)]
+pub struct SolutionGroup {
+    pub reactions: Vec<ReactionKind>,
+    pub solutions: Vec<Vec<LevelPlacement>>,
+    /// Whether one of the level's reference solutions is in this group
+    pub is_reference: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct SolutionAnalysis {
+    /// Groups of solutions, fewest atoms first
+    pub groups: Vec<SolutionGroup>,
+    pub max_atoms: usize,
+    /// Whether every layout within the limits was tried. If not, there may be more solutions.
+    pub exhaustive: bool,
+}
+
+/// Find every solution within the limits that doesn't contain a smaller solution, and group them
+/// by the reactions they cause. The level's reference solutions are always included.
+pub fn find_solutions(level: &Level, limits: &SolverLimits) -> SolutionAnalysis {
+    let mut groups: Vec<SolutionGroup> = Vec::new();
+    let mut add_solution =
+        |layout: &[(IVec2, PlacedAtom)], reactions: &[ReactionEvent], is_reference: bool| {
+            let reactions: Vec<_> = reactions.iter().map(ReactionKind::of).collect();
+            let solution: Vec<_> = layout
+                .iter()
+                .map(|&(position, placed)| LevelPlacement {
+                    atom_type: placed.atom_type,
+                    position,
+                    direction: placed.direction,
+                })
+                .collect();
+            let group = match groups.iter().position(|group| group.reactions == reactions) {
+                Some(index) => &mut groups[index],
+                None => {
+                    groups.push(SolutionGroup {
+                        reactions,
+                        solutions: Vec::new(),
+                        is_reference: false,
+                    });
+                    groups.last_mut().unwrap()
+                }
+            };
+            group.is_reference |= is_reference;
+            if !group
+                .solutions
+                .iter()
+                .any(|existing| same_layout(existing, &solution))
+            {
+                group.solutions.push(solution);
+            }
+        };
+
+    let exhaustive = search(level, limits, |layout, report| {
+        if report.is_complete() {
+            add_solution(layout, &report.reactions, false);
+        }
+        SearchControl::Continue
+    });
+    for reference in &level.solutions {
+        let layout = LevelPlacement::layout(reference);
+        let report = simulate(level, &layout);
+        if report.is_complete() {
+            add_solution(&layout, &report.reactions, true);
+        }
+    }
+
+    SolutionAnalysis {
+        groups,
+        max_atoms: limits.max_atoms,
+        exhaustive,
+    }
+}
+
+fn same_layout(a: &[LevelPlacement], b: &[LevelPlacement]) -> bool {
+    a.len() == b.len()
+        && a.iter().all(|placement| {
+            b.iter().any(|other| {
+                placement.position == other.position
+                    && placement.atom_type == other.atom_type
+                    && placement.direction == other.direction
+            })
+        })
+}
+
+impl SolutionAnalysis {
+    /// Groups that don't include any of the level's reference solutions, so may skip the idea the
+    /// level is meant to teach. Levels without a working reference solution have none.
+    pub fn unintended(&self) -> impl Iterator<Item = &SolutionGroup> {
+        let has_reference = self.groups.iter().any(|group| group.is_reference);
+        self.groups
+            .iter()
+            .filter(move |group| has_reference && !group.is_reference)
+    }
+
+    /// A summary of each group of solutions, with an example of each
+    pub fn describe(&self) -> String {
+        let has_reference = self.groups.iter().any(|group| group.is_reference);
+        let solution_count: usize = self.groups.iter().map(|group| group.solutions.len()).sum();
+        let mut lines = vec![format!(
+            "{solution_count} solution{} in {} group{} with up to {} atoms{}",
+            if solution_count == 1 { "" } else { "s" },
+            self.groups.len(),
+            if self.groups.len() == 1 { "" } else { "s" },
+            self.max_atoms,
+            if self.exhaustive {
+                ""
+            } else {
+                " (search stopped early)"
+            }
+        )];
+        for (index, group) in self.groups.iter().enumerate() {
+            lines.push(format!(
+                "{}. {} solution{}, {} reaction{}{}",
+                index + 1,
+                group.solutions.len(),
+                if group.solutions.len() == 1 { "" } else { "s" },
+                group.reactions.len(),
+                if group.reactions.len() == 1 { "" } else { "s" },
+                if group.is_reference {
+                    " (reference)"
+                } else if has_reference {
+                    " (unintended)"
+                } else {
+                    ""
+                }
+            ));
+            for reaction in describe_reactions(&group.reactions) {
+                lines.push(format!("   {reaction}"));
+            }
+            for solution in group.solutions.iter().take(EXAMPLES_PER_GROUP) {
+                lines.push(format!("   e.g. {}", describe_solution(solution)));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+/// Describe a sequence of reactions, collapsing repeats into e.g. "2x Basic + Basic → Splitting"
+fn describe_reactions(reactions: &[ReactionKind]) -> Vec<String> {
+    reactions
+        .chunk_by(|a, b| a == b)
+        .map(|repeats| match repeats.len() {
+            1 => repeats[0].describe(),
+            count => format!("{count}x {}", repeats[0].describe()),
+        })
+        .collect()
+}
+
+/// e.g. "Splitting (2, 0), Basic (3, 1)"
+fn describe_solution(solution: &[LevelPlacement]) -> String {
+    solution
+        .iter()
+        .map(|placement| {
+            format!(
+                "{:?} ({}, {}){}",
+                placement.atom_type,
+                placement.position.x,
+                placement.position.y,
+                placement
+                    .direction
+                    .map(|direction| format!(" {direction:?}"))
+                    .unwrap_or_default()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        level::{LevelAtom, LevelGoal, LevelGoalPosition},
+        movement::CardinalDirection,
+    };
+
+    fn placement(atom_type: AtomType, x: i32, y: i32) -> LevelPlacement {
+        LevelPlacement {
+            atom_type,
+            position: IVec2::new(x, y),
+            direction: None,
+        }
+    }
+
+    #[test]
+    fn other_solutions_are_reported_as_unintended() {
+        // The intended solution splits the atom, but fusing it first and then splitting it works
+        // too
+        let level = Level {
+            atoms: vec![LevelAtom::new_with_velocity(
+                AtomType::Basic,
+                (-3, 0),
+                CardinalDirection::E,
+            )],
+            goal: LevelGoal::ReachPositions(vec![LevelGoalPosition {
+                atom_type: AtomType::Basic,
+                position: IVec2::new(2, 2),
+            }]),
+            placeable_atoms: vec![AtomType::Basic, AtomType::Splitting],
+            solutions: vec![vec![placement(AtomType::Splitting, 0, 0)]],
+            ..default()
+        };
+        let limits = SolverLimits {
+            max_atoms: 2,
+            region: IRect::new(-3, -3, 3, 3),
+            ..SolverLimits::for_level(&level)
+        };
+        let analysis = find_solutions(&level, &limits);
+        assert!(analysis.exhaustive);
+
+        let reference = analysis
+            .groups
+            .iter()
+            .find(|group| group.is_reference)
+            .expect("The reference solution should be found");
+        assert_eq!(reference.reactions.len(), 1);
+
+        let fused_first = [
+            placement(AtomType::Basic, -2, 0),
+            placement(AtomType::Basic, 0, 0),
+        ];
+        let unintended: Vec<_> = analysis.unintended().collect();
+        assert!(
+            unintended.iter().any(|group| group
+                .solutions
+                .iter()
+                .any(|solution| same_layout(solution, &fused_first))),
+            "{}",
+            analysis.describe()
+        );
+        assert!(analysis.describe().contains("(unintended)"));
+    }
+}
@@ -1,17 +1,29 @@
 use bevy::{prelude::*, render::view::RenderLayers};
 use serde::{Deserialize, Serialize};
 
-use crate::screens::Screen;
+use crate::{screens::Screen, theme::palette::OFF_WHITE};
 
 use super::{animation::Animated, placement::GridPos};
 
-#[derive(Debug, Clone, Copy, Component, PartialEq, Eq, PartialOrd, Serialize, Deserialize)]
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        update_wall_damage.run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(
+    Debug, Clone, Copy, Component, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
 pub enum AtomType {
     Basic,
     Splitting,
     Wall,
     Reactive,
     Antimatter,
+    /// One end of a portal pair. Atoms entering a portal leave from the other portal with the
+    /// same id.
+    Portal(u8),
 }
 
 impl AtomType {
@@ -22,12 +34,33 @@ impl AtomType {
             AtomType::Wall => atom_assets.wall.clone(),
             AtomType::Reactive => atom_assets.reactive.clone(),
             AtomType::Antimatter => atom_assets.antimatter.clone(),
+            AtomType::Portal(_) => atom_assets.circle.clone(),
+        }
+    }
+
+    /// Tint applied to the atom's image. Portals are coloured by their id so that pairs can be
+    /// told apart.
+    pub fn get_color(&self) -> Color {
+        match self {
+            AtomType::Portal(id) => match id % 4 {
+                0 => Color::srgb(0.4, 0.8, 1.0),
+                1 => Color::srgb(1.0, 0.6, 0.2),
+                2 => Color::srgb(0.6, 1.0, 0.4),
+                _ => Color::srgb(1.0, 0.4, 0.8),
+            },
+            _ => Color::WHITE,
         }
     }
 
     pub fn get_sprite(&self, atom_assets: &AtomAssets) -> Sprite {
         match self {
             AtomType::Wall => Sprite::from_image(self.get_image_handle(atom_assets)),
+            AtomType::Portal(_) => Sprite {
+                image: self.get_image_handle(atom_assets),
+                color: self.get_color(),
+                custom_size: Some(Vec2::splat(PORTAL_SIZE)),
+                ..Default::default()
+            },
             _ => Sprite::from_atlas_image(
                 self.get_image_handle(atom_assets),
                 TextureAtlas {
@@ -46,6 +79,12 @@ impl AtomType {
                     ..Default::default()
                 }
             }
+            AtomType::Portal(_) => Sprite {
+                image: self.get_image_handle(atom_assets),
+                color: self.get_color().with_alpha(0.5),
+                custom_size: Some(Vec2::splat(PORTAL_SIZE)),
+                ..Default::default()
+            },
             _ => {
                 Sprite {
                     image: self.get_image_handle(atom_assets),
@@ -61,6 +100,13 @@ impl AtomType {
     }
 }
 
+/// Number of portal pairs that can be told apart by colour. The sandbox cycles through this many
+/// portal ids.
+pub const PORTAL_IDS: u8 = 4;
+
+/// Size of the portal sprite in pixels, chosen to roughly match the size of an atom frame.
+const PORTAL_SIZE: f32 = 300.0;
+
 #[derive(Resource, Asset, Clone, Reflect)]
 #[reflect(Resource)]
 pub struct AtomAssets {
@@ -77,6 +123,10 @@ pub struct AtomAssets {
     pub antimatter: Handle<Image>,
     #[dependency]
     pub circle: Handle<Image>,
+    pub wall_damage_layout: Handle<TextureAtlasLayout>,
+    /// Walls at each stage of damage, from intact to about to break
+    #[dependency]
+    pub wall_damage: Handle<Image>,
 }
 
 impl FromWorld for AtomAssets {
@@ -91,6 +141,16 @@ impl FromWorld for AtomAssets {
                     None,
                     None,
                 ));
+        let wall_damage_layout =
+            world
+                .resource_mut::<Assets<TextureAtlasLayout>>()
+                .add(TextureAtlasLayout::from_grid(
+                    UVec2::new(364, 304),
+                    WALL_DAMAGE_STAGES,
+                    1,
+                    None,
+                    None,
+                ));
         let assets = world.resource::<AssetServer>();
         Self {
             atlas_layout,
@@ -100,6 +160,8 @@ impl FromWorld for AtomAssets {
             reactive: assets.load("images/atom4.png"),
             antimatter: assets.load("images/atom5.png"),
             circle: assets.load("images/circle.png"),
+            wall_damage_layout,
+            wall_damage: assets.load("images/wall_damage.png"),
         }
     }
 }
@@ -117,3 +179,67 @@ pub fn atom(atom_type: AtomType, position: IVec2, atom_assets: &AtomAssets) -> i
         StateScoped(Screen::Gameplay),
     )
 }
+
+/// Number of impacts a wall can take before it breaks. Walls without this component are
+/// indestructible.
+#[derive(Component)]
+pub struct Durability {
+    pub remaining: u32,
+    pub max: u32,
+}
+
+#[derive(Component)]
+struct DurabilityLabel;
+
+pub fn durability(hits: u32) -> impl Bundle {
+    (
+        Durability {
+            remaining: hits,
+            max: hits,
+        },
+        children![(
+            Name::new("Durability Label"),
+            Text2d::new(hits.to_string()),
+            TextFont::from_font_size(32.0),
+            TextColor(OFF_WHITE),
+            Transform::from_xyz(0.0, 0.0, 2.0).with_scale(Vec3::splat(5.0)),
+            RenderLayers::layer(2),
+            DurabilityLabel,
+        )],
+    )
+}
+
+/// Number of frames in the wall damage sprite sheet
+const WALL_DAMAGE_STAGES: u32 = 3;
+
+/// Shows cracks in walls as their durability runs down
+fn update_wall_damage(
+    mut commands: Commands,
+    mut walls: Query<(&Durability, &mut Sprite), Changed<Durability>>,
+    mut labels: Query<(&ChildOf, &mut Text2d), With<DurabilityLabel>>,
+    atom_assets: Res<AtomAssets>,
+) {
+    for (child_of, mut text) in &mut labels {
+        if let Ok((durability, mut sprite)) = walls.get_mut(child_of.parent()) {
+            let max = durability.max.max(1);
+            let damage = max.saturating_sub(durability.remaining);
+            let stage = (damage * (WALL_DAMAGE_STAGES - 1))
+                .div_ceil(max)
+                .min(WALL_DAMAGE_STAGES - 1);
+            if stage == 0 {
+                *sprite = AtomType::Wall.get_sprite(&atom_assets);
+            } else {
+                *sprite = Sprite::from_atlas_image(
+                    atom_assets.wall_damage.clone(),
+                    TextureAtlas {
+                        layout: atom_assets.wall_damage_layout.clone(),
+                        index: stage as usize,
+                    },
+                );
+                // The stage is picked here, so the frame mustn't be animated
+                commands.entity(child_of.parent()).remove::<Animated>();
+            }
+            text.0 = durability.remaining.to_string();
+        }
+    }
+}
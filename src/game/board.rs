@@ -0,0 +1,345 @@
+//! Draws a level, or an experiment in progress, as a grid of text with north at the top. Boards
+//! are plain text so that they can be printed by the level tool, written to the log, pasted into
+//! bug reports, and compared in tests and diffs.
+//!
+//! Each grid cell is two characters wide: what's in the cell, then the direction it's moving or
+//! facing. See [`LEGEND`].
+
+use std::fmt;
+
+use bevy::prelude::*;
+
+use super::{
+    atom::AtomType,
+    level::{Level, LevelGoal, LevelPlacement},
+    movement::CardinalDirection,
+    simulation::Simulation,
+    solver::level_bounds,
+};
+
+/// What each character on a board means
+pub const LEGEND: &str = "\
+. empty   B basic   S splitting   # wall   R reactive   A antimatter   0-9 portal   P portal 10+
+oX goal for an X atom   E emitter   D detector   * switch   = closed gate   _ open gate
+^ > v < moving or facing N E S W   ↗ ↘ ↙ ↖ moving NE SE SW NW";
+
+pub(super) const EMPTY: char = '.';
+pub(super) const GOAL: char = 'o';
+pub(super) const EMITTER: char = 'E';
+pub(super) const DETECTOR: char = 'D';
+pub(super) const SWITCH: char = '*';
+pub(super) const CLOSED_GATE: char = '=';
+pub(super) const OPEN_GATE: char = '_';
+/// Second character of cells that aren't moving or facing anywhere
+pub(super) const STILL: char = ' ';
+
+/// The character drawn for an atom. Portals are drawn as their id, apart from ids too long to fit
+/// in a cell, which are all drawn as `P`.
+pub fn atom_glyph(atom_type: AtomType) -> char {
+    match atom_type {
+        AtomType::Basic => 'B',
+        AtomType::Splitting => 'S',
+        AtomType::Wall => '#',
+        AtomType::Reactive => 'R',
+        AtomType::Antimatter => 'A',
+        AtomType::Portal(id) => char::from_digit(u32::from(id), 10).unwrap_or('P'),
+    }
+}
+
+/// The atom a character is drawn for, the reverse of [`atom_glyph`]
+pub fn atom_from_glyph(glyph: char) -> Option<AtomType> {
+    match glyph {
+        'B' => Some(AtomType::Basic),
+        'S' => Some(AtomType::Splitting),
+        '#' => Some(AtomType::Wall),
+        'R' => Some(AtomType::Reactive),
+        'A' => Some(AtomType::Antimatter),
+        _ => glyph.to_digit(10).map(|id| AtomType::Portal(id as u8)),
+    }
+}
+
+/// The arrow drawn after something moving or facing in a direction
+pub fn direction_glyph(direction: CardinalDirection) -> char {
+    match direction {
+        CardinalDirection::N => '^',
+        CardinalDirection::E => '>',
+        CardinalDirection::S => 'v',
+        CardinalDirection::W => '<',
+        CardinalDirection::NE => '↗',
+        CardinalDirection::SE => '↘',
+        CardinalDirection::SW => '↙',
+        CardinalDirection::NW => '↖',
+    }
+}
+
+/// The direction an arrow points in, the reverse of [`direction_glyph`]
+pub fn direction_from_glyph(glyph: char) -> Option<CardinalDirection> {
+    match glyph {
+        '^' => Some(CardinalDirection::N),
+        '>' => Some(CardinalDirection::E),
+        'v' => Some(CardinalDirection::S),
+        '<' => Some(CardinalDirection::W),
+        '↗' => Some(CardinalDirection::NE),
+        '↘' => Some(CardinalDirection::SE),
+        '↙' => Some(CardinalDirection::SW),
+        '↖' => Some(CardinalDirection::NW),
+        _ => None,
+    }
+}
+
+/// A grid of cells covering part of the level
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Board {
+    bounds: IRect,
+    /// Rows from north to south
+    cells: Vec<Vec<[char; 2]>>,
+}
+
+impl Board {
+    /// An empty board covering the given cells
+    pub fn new(bounds: IRect) -> Self {
+        let width = (bounds.width() + 1) as usize;
+        let height = (bounds.height() + 1) as usize;
+        Self {
+            bounds,
+            cells: vec![vec![[EMPTY, STILL]; width]; height],
+        }
+    }
+
+    /// A board big enough for the level and the placed atoms, with a border of empty cells
+    pub fn for_level(level: &Level, placements: &[LevelPlacement]) -> Self {
+        let bounds = placements
+            .iter()
+            .fold(level_bounds(level), |bounds, placement| {
+                bounds.union_point(placement.position)
+            });
+        // A level with nothing in it is drawn around the origin
+        let bounds = if bounds == IRect::EMPTY {
+            IRect::new(0, 0, 0, 0)
+        } else {
+            bounds
+        };
+        Self::new(bounds.inflate(1))
+    }
+
+    pub fn bounds(&self) -> IRect {
+        self.bounds
+    }
+
+    /// Draw a cell, replacing whatever was there. Cells off the board are ignored.
+    pub fn draw(&mut self, position: IVec2, cell: [char; 2]) {
+        if !self.bounds.contains(position) {
+            return;
+        }
+        let row = (self.bounds.max.y - position.y) as usize;
+        let column = (position.x - self.bounds.min.x) as usize;
+        self.cells[row][column] = cell;
+    }
+
+    pub fn draw_atom(
+        &mut self,
+        position: IVec2,
+        atom_type: AtomType,
+        direction: Option<CardinalDirection>,
+    ) {
+        self.draw(
+            position,
+            [
+                atom_glyph(atom_type),
+                direction.map_or(STILL, direction_glyph),
+            ],
+        );
+    }
+
+    /// Draw everything in the level except its atoms, which move during an experiment
+    fn draw_fixtures(&mut self, level: &Level) {
+        for emitter in &level.emitters {
+            self.draw(
+                emitter.position,
+                [EMITTER, direction_glyph(emitter.direction)],
+            );
+        }
+        for detector in &level.detectors {
+            self.draw(
+                detector.position,
+                [DETECTOR, detector.direction.map_or(STILL, direction_glyph)],
+            );
+        }
+        for switch in &level.switches {
+            self.draw(switch.position, [SWITCH, STILL]);
+        }
+    }
+
+    fn draw_goal(&mut self, position: IVec2, atom_type: AtomType) {
+        self.draw(position, [GOAL, atom_glyph(atom_type)]);
+    }
+
+    /// Draw a level as it is before the experiment starts, with some atoms placed
+    pub fn draw_level(&mut self, level: &Level, placements: &[LevelPlacement]) {
+        self.draw_fixtures(level);
+        for gate in &level.gates {
+            let glyph = if gate.open { OPEN_GATE } else { CLOSED_GATE };
+            self.draw(gate.position, [glyph, STILL]);
+        }
+        if let LevelGoal::ReachPositions(goals) = &level.goal {
+            for goal in goals {
+                self.draw_goal(goal.position, goal.atom_type);
+            }
+        }
+        for atom in &level.atoms {
+            self.draw_atom(
+                atom.position,
+                atom.atom_type,
+                atom.velocity.as_ref().map(|movement| movement.direction),
+            );
+        }
+        for placement in placements {
+            self.draw_atom(placement.position, placement.atom_type, placement.direction);
+        }
+    }
+
+    /// Draw an experiment in progress. Goals that have been reached are left out, and atoms
+    /// between cells are drawn in the nearest one.
+    pub fn draw_simulation(&mut self, simulation: &Simulation) {
+        self.draw_fixtures(simulation.level());
+        // Closed gates are atoms in the simulation, so are drawn over these
+        for gate in &simulation.level().gates {
+            self.draw(gate.position, [OPEN_GATE, STILL]);
+        }
+        for &(atom_type, position) in simulation.remaining_goals() {
+            self.draw_goal(position, atom_type);
+        }
+        for atom in simulation.atoms() {
+            let position = atom.position.round().as_ivec2();
+            if atom.is_gate() {
+                self.draw(position, [CLOSED_GATE, STILL]);
+            } else {
+                self.draw_atom(position, atom.atom_type, atom.direction);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Board {
+    /// The board, preceded by a line giving the positions of its top left and bottom right cells
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "({}, {}) to ({}, {})",
+            self.bounds.min.x, self.bounds.max.y, self.bounds.max.x, self.bounds.min.y
+        )?;
+        for row in &self.cells {
+            let line: String = row.iter().flatten().collect();
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+/// Draw a level with some atoms placed
+pub fn render_level(level: &Level, placements: &[LevelPlacement]) -> String {
+    let mut board = Board::for_level(level, placements);
+    board.draw_level(level, placements);
+    board.to_string()
+}
+
+/// Draw an experiment in progress, on a board covering the given cells
+pub fn render_simulation(simulation: &Simulation, bounds: IRect) -> String {
+    let mut board = Board::new(bounds);
+    board.draw_simulation(simulation);
+    board.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::level::{
+        LevelAtom, LevelDetector, LevelEmitter, LevelGate, LevelGoalPosition, LevelSwitch,
+    };
+
+    fn fixtures_level() -> Level {
+        Level {
+            atoms: vec![
+                LevelAtom::new(AtomType::Wall, (0, 0)),
+                LevelAtom::new(AtomType::Portal(1), (1, 2)),
+                LevelAtom::new(AtomType::Portal(1), (4, 2)),
+                LevelAtom::new_with_velocity(AtomType::Basic, (0, 1), CardinalDirection::E),
+            ],
+            goal: LevelGoal::ReachPositions(vec![LevelGoalPosition {
+                atom_type: AtomType::Basic,
+                position: IVec2::new(5, 1),
+            }]),
+            placeable_atoms: vec![AtomType::Splitting],
+            emitters: vec![LevelEmitter {
+                atom_type: AtomType::Reactive,
+                position: IVec2::new(-1, 2),
+                direction: CardinalDirection::S,
+                interval: 4,
+                count: None,
+            }],
+            detectors: vec![LevelDetector {
+                atom_type: AtomType::Basic,
+                position: IVec2::new(3, 0),
+                direction: Some(CardinalDirection::E),
+                target: None,
+            }],
+            switches: vec![LevelSwitch {
+                position: IVec2::new(2, -1),
+                atom_type: None,
+                gates: vec![IVec2::new(4, 0), IVec2::new(4, -1)],
+            }],
+            gates: vec![
+                LevelGate {
+                    position: IVec2::new(4, 0),
+                    open: false,
+                },
+                LevelGate {
+                    position: IVec2::new(4, -1),
+                    open: true,
+                },
+            ],
+            ..default()
+        }
+    }
+
+    #[test]
+    fn render_level_draws_everything() {
+        let placements = [LevelPlacement {
+            atom_type: AtomType::Splitting,
+            position: IVec2::new(2, 1),
+            direction: None,
+        }];
+        assert_eq!(
+            render_level(&fixtures_level(), &placements),
+            "\
+(-2, 3) to (6, -2)
+. . . . . . . . .
+. Ev. 1 . . 1 . .
+. . B>. S . . oB.
+. . # . . D>= . .
+. . . . * . _ . .
+. . . . . . . . .
+"
+        );
+    }
+
+    #[test]
+    fn render_simulation_draws_atoms_where_they_are() {
+        let level = fixtures_level();
+        let mut simulation = Simulation::new(&level, &[]);
+        // Half a second, long enough for atoms to move one cell
+        for _ in 0..30 {
+            simulation.step();
+        }
+        assert_eq!(
+            render_simulation(&simulation, IRect::new(-1, -1, 5, 2)),
+            "\
+(-1, 2) to (5, -1)
+Ev. 1 . . 1 .
+. . B>. . . oB
+. # . . D>= .
+. . . * . _ .
+"
+        );
+    }
+}
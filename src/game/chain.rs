@@ -0,0 +1,93 @@
+//! Tracks how chain reactions unfold. Atoms created by reactions remember how many reactions led
+//! to them and which of the player's placed atoms started the chain.
+
+use bevy::prelude::*;
+
+use crate::{AppSystems, PausableSystems};
+
+use super::{collision::ReactionEvent, state::GameState};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ChainStats>();
+    app.add_systems(OnEnter(GameState::Running), reset_chain_stats);
+    app.add_systems(
+        Update,
+        update_chain_stats
+            .run_if(in_state(GameState::Running))
+            .in_set(AppSystems::Update)
+            .in_set(PausableSystems),
+    );
+}
+
+/// Where an atom came from. Atoms without this component are part of the level itself.
+#[derive(Component, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Lineage {
+    /// Number of reactions in the chain that produced this atom. Placed atoms have a depth of 0.
+    pub depth: u32,
+    /// Grid position of the placed atom that started the chain, if there was one
+    pub origin: Option<IVec2>,
+}
+
+impl Lineage {
+    /// Lineage of an atom placed by the player
+    pub fn placed(position: IVec2) -> Self {
+        Self {
+            depth: 0,
+            origin: Some(position),
+        }
+    }
+
+    /// Lineage of the products of a reaction between atoms with the given lineages. Atoms
+    /// without lineage count as depth 0.
+    pub fn descend(inputs: &[Option<Lineage>]) -> Self {
+        let parent = inputs
+            .iter()
+            .flatten()
+            .max_by_key(|lineage| (lineage.origin.is_some(), lineage.depth))
+            .copied()
+            .unwrap_or_default();
+        let depth = inputs
+            .iter()
+            .map(|lineage| lineage.map_or(0, |lineage| lineage.depth))
+            .max()
+            .unwrap_or(0);
+        Self {
+            depth: depth + 1,
+            origin: parent.origin,
+        }
+    }
+}
+
+/// Chain reaction statistics for the current experiment
+#[derive(Resource, Default, Debug)]
+pub struct ChainStats {
+    /// Deepest chain among atoms that still exist
+    pub current_depth: u32,
+    /// Deepest chain seen during the experiment
+    pub max_depth: u32,
+    pub reactions: u32,
+}
+
+fn reset_chain_stats(mut stats: ResMut<ChainStats>) {
+    *stats = ChainStats::default();
+}
+
+fn update_chain_stats(
+    mut stats: ResMut<ChainStats>,
+    mut reactions: EventReader<ReactionEvent>,
+    lineages: Query<&Lineage>,
+) {
+    for reaction in reactions.read() {
+        stats.reactions += 1;
+        stats.max_depth = stats.max_depth.max(reaction.depth);
+    }
+    let current_depth = lineages
+        .iter()
+        .map(|lineage| lineage.depth)
+        .max()
+        .unwrap_or(0);
+    // Only write when it changes, to avoid triggering change detection every frame
+    if stats.current_depth != current_depth {
+        stats.current_depth = current_depth;
+    }
+}
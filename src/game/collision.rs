@@ -1,4 +1,4 @@
-use bevy::{platform::collections::HashMap, prelude::*};
+use bevy::{platform::collections::HashMap, prelude::*, render::view::RenderLayers};
 
 use crate::{
     AppSystems, PausableSystems,
@@ -6,19 +6,34 @@ use crate::{
 };
 
 use super::{
-    atom::{AtomAssets, AtomType, atom},
+    atom::{AtomAssets, AtomType, Durability, atom},
+    chain::Lineage,
     level::LevelEntity,
-    movement::{CardinalDirection, Movement},
-    state::GameState,
+    movement::Movement,
+    placement::{DraggingGhost, GridPos},
+    reaction::{Decay, Reactant, ReactionSound, react},
+    state::{ExperimentSystems, GameState},
+    switch::Gate,
+    tick::ExperimentClock,
 };
 
 pub(super) fn plugin(app: &mut App) {
+    app.add_event::<ReactionEvent>();
+    app.init_resource::<NextSpawnOrder>();
     app.add_observer(handle_collision);
+    app.add_observer(number_spawned_atom);
+    app.add_systems(OnEnter(GameState::Running), number_starting_atoms);
     app.add_systems(
         Update,
-        detect_atom_collisions
+        (
+            detect_atom_collisions,
+            decay_unstable_atoms,
+            update_decay_rings,
+        )
+            .chain()
             .run_if(in_state(GameState::Running))
             .in_set(AppSystems::Update)
+            .in_set(ExperimentSystems::React)
             .in_set(PausableSystems),
     );
     app.add_systems(
@@ -29,332 +44,420 @@ pub(super) fn plugin(app: &mut App) {
     );
 }
 
+/// The order atoms entered the experiment in. Atoms that collide at the same time are found in this
+/// order, which decides whose direction the products of a reaction follow, so it must match the
+/// order of the atoms in the headless [`Simulation`](super::simulation::Simulation).
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SpawnOrder(u32);
+
+#[derive(Resource, Default)]
+struct NextSpawnOrder(u32);
+
+fn number_spawned_atom(
+    trigger: Trigger<OnAdd, AtomType>,
+    mut commands: Commands,
+    mut next_order: ResMut<NextSpawnOrder>,
+) {
+    commands
+        .entity(trigger.target())
+        .try_insert(SpawnOrder(next_order.0));
+    next_order.0 += 1;
+}
+
+/// Number the atoms the experiment starts with in the order the simulation sets them up in: the
+/// level's atoms, then the placed atoms by position, then closed gates. Atoms are placed in
+/// whatever order the player likes, so their spawn order can't be used as it is.
+fn number_starting_atoms(
+    mut commands: Commands,
+    mut next_order: ResMut<NextSpawnOrder>,
+    atoms: Query<
+        (
+            Entity,
+            &GridPos,
+            Option<&SpawnOrder>,
+            Has<LevelEntity>,
+            Has<Gate>,
+        ),
+        (With<AtomType>, Without<DraggingGhost>),
+    >,
+) {
+    let mut atoms: Vec<_> = atoms.iter().collect();
+    atoms.sort_by_key(|&(_, grid_pos, order, is_level_atom, is_gate)| {
+        let order = order.map_or(u32::MAX, |order| order.0);
+        match (is_gate, is_level_atom) {
+            (true, _) => (2, 0, 0, order),
+            (false, true) => (0, 0, 0, order),
+            (false, false) => (1, grid_pos.0.x, grid_pos.0.y, 0),
+        }
+    });
+    next_order.0 = 0;
+    for (entity, ..) in atoms {
+        commands.entity(entity).insert(SpawnOrder(next_order.0));
+        next_order.0 += 1;
+    }
+}
+
 #[derive(Event)]
 pub struct CollisionEvent {
     pub entities: Vec<Entity>,
     pub position: IVec2,
 }
 
+/// Sent whenever atoms react with each other, or an unstable atom decays. Atoms bouncing off walls
+/// and gates aren't reactions.
+#[derive(Event, Debug, Clone)]
+pub struct ReactionEvent {
+    /// The experiment tick that the reaction happened on
+    pub tick: u32,
+    pub position: IVec2,
+    pub inputs: Vec<AtomType>,
+    pub outputs: Vec<AtomType>,
+    /// Chain depth of the atoms produced by the reaction
+    pub depth: u32,
+}
+
+impl ReactionEvent {
+    /// e.g. "t12 (3, -1): Basic + Basic → Splitting"
+    pub fn describe(&self) -> String {
+        let join = |atoms: &[_]| {
+            if atoms.is_empty() {
+                "nothing".to_string()
+            } else {
+                atoms
+                    .iter()
+                    .map(|atom_type| format!("{atom_type:?}"))
+                    .collect::<Vec<_>>()
+                    .join(" + ")
+            }
+        };
+        format!(
+            "t{} ({}, {}): {} → {}",
+            self.tick,
+            self.position.x,
+            self.position.y,
+            join(&self.inputs),
+            join(&self.outputs)
+        )
+    }
+}
+
 fn handle_collision(
     trigger: Trigger<CollisionEvent>,
     mut commands: Commands,
     atom_assets: Res<AtomAssets>,
-    atom_query: Query<(&AtomType, Option<&Movement>)>,
+    atom_query: Query<(&AtomType, Option<&Movement>, Option<&Lineage>)>,
+    portal_query: Query<(&AtomType, &GridPos)>,
+    mut durability_query: Query<&mut Durability>,
     audio_assets: Res<AudioAssets>,
+    clock: Res<ExperimentClock>,
+    mut reaction_events: EventWriter<ReactionEvent>,
 ) {
     let event = trigger.event();
 
+    let portal_id = event
+        .entities
+        .iter()
+        .find_map(|&entity| match atom_query.get(entity) {
+            Ok((AtomType::Portal(id), _, _)) => Some(*id),
+            _ => None,
+        });
+
     if event.entities.len() < 2 {
         return;
-    } else if event.entities.len() >= 3 {
-        // Despawn all colliding atoms
+    } else if let Some(portal_id) = portal_id {
+        // Find the other end of the portal pair
+        let Some(exit_position) = portal_query.iter().find_map(|(atom_type, grid_pos)| {
+            (*atom_type == AtomType::Portal(portal_id) && grid_pos.0 != event.position)
+                .then_some(grid_pos.0)
+        }) else {
+            // Unlinked portals have no effect
+            return;
+        };
         for &entity in &event.entities {
-            commands.entity(entity).despawn();
+            if let Ok((&atom_type, Some(movement), lineage)) = atom_query.get(entity) {
+                handle_portal_collision(
+                    entity,
+                    atom_type,
+                    movement,
+                    lineage,
+                    exit_position,
+                    &mut commands,
+                    &atom_assets,
+                );
+            }
         }
-        // 3 way collision always spawns a (motionless) reactive atom
-        commands.spawn((
-            atom(AtomType::Reactive, event.position, &atom_assets),
-            LevelEntity,
-            CollisionCooldown::default(),
-        ));
-        commands.spawn(sound_effect(audio_assets.split_big_sfx.clone()));
+        commands.spawn(sound_effect(audio_assets.hit_sfx.clone()));
         return;
     }
 
-    // We know there are exactly 2 entities at this point
-    let [entity1, entity2] = [event.entities[0], event.entities[1]];
-
-    let Ok((atom_type1, movement1)) = atom_query.get(entity1) else {
+    let Ok(atoms) = event
+        .entities
+        .iter()
+        .map(|&entity| atom_query.get(entity))
+        .collect::<Result<Vec<_>, _>>()
+    else {
         return;
     };
-    let Ok((atom_type2, movement2)) = atom_query.get(entity2) else {
+    let reactants: Vec<Reactant> = atoms
+        .iter()
+        .map(|(atom_type, movement, _)| (**atom_type, movement.map(|m| m.direction)))
+        .collect();
+    let lineages: Vec<Option<Lineage>> = atoms
+        .iter()
+        .map(|(_, _, lineage)| lineage.copied())
+        .collect();
+    if reactants.iter().all(|(_, direction)| direction.is_none()) {
+        warn!("Collision between two stationary atoms. This shouldn't happen.");
+    }
+
+    let Some(outcome) = react(&reactants) else {
         return;
     };
 
-    // Handle collision based on atom types
-    match (atom_type1, atom_type2) {
-        // Wall and antimatter - destroy both
-        (AtomType::Wall, AtomType::Antimatter) | (AtomType::Antimatter, AtomType::Wall) => {
-            commands.entity(entity1).despawn();
-            commands.entity(entity2).despawn();
-
-            commands.spawn(sound_effect(audio_assets.merge_sfx.clone()));
-        }
-
-        // Wall collisions - bounce the non-wall atom
-        (AtomType::Wall, other_type) => {
-            handle_wall_collision(
-                entity2,
-                *other_type,
-                movement2,
-                event.position,
-                &mut commands,
-                &atom_assets,
-            );
-            commands.spawn(sound_effect(audio_assets.hit_sfx.clone()));
+    for ((&entity, &(atom_type, _)), &destroyed) in event
+        .entities
+        .iter()
+        .zip(&reactants)
+        .zip(&outcome.destroyed)
+    {
+        if destroyed {
+            commands.entity(entity).despawn();
+        } else if atom_type == AtomType::Wall {
+            damage_wall(entity, &mut durability_query, &mut commands);
         }
-        (other_type, AtomType::Wall) => {
-            handle_wall_collision(
-                entity1,
-                *other_type,
-                movement1,
-                event.position,
-                &mut commands,
-                &atom_assets,
-            );
-            commands.spawn(sound_effect(audio_assets.hit_sfx.clone()));
+    }
+    // Atoms bouncing off walls keep their lineage, as they haven't really reacted
+    let product_lineage = match (&reactants[..], &lineages[..]) {
+        ([(AtomType::Wall, _), _], [_, lineage]) | ([_, (AtomType::Wall, _)], [lineage, _]) => {
+            *lineage
         }
+        _ => Some(Lineage::descend(&lineages)),
+    };
+    let speed = outcome
+        .keep_speed_of
+        .and_then(|index| atoms[index].1)
+        .map(|movement| movement.speed);
+    for &product in &outcome.products {
+        spawn_product(
+            &mut commands,
+            product,
+            speed,
+            outcome.cooldown,
+            product_lineage,
+            event.position,
+            &atom_assets,
+        );
+    }
 
-        // Two basic atoms fuse into a splitting atom
-        (AtomType::Basic, AtomType::Basic) => {
-            commands.entity(entity1).despawn();
-            commands.entity(entity2).despawn();
-
-            let movement = movement1.cloned().or_else(|| movement2.cloned());
+    commands.spawn(sound_effect(reaction_sound(outcome.sound, &audio_assets)));
+    if outcome.bounce {
+        return;
+    }
+    reaction_events.write(ReactionEvent {
+        tick: clock.tick(),
+        position: event.position,
+        inputs: reactants.iter().map(|(atom_type, _)| *atom_type).collect(),
+        outputs: outcome
+            .products
+            .iter()
+            .map(|(atom_type, _)| *atom_type)
+            .collect(),
+        depth: product_lineage.map_or(0, |lineage| lineage.depth),
+    });
+}
 
-            let mut entity = commands.spawn((
-                atom(AtomType::Splitting, event.position, &atom_assets),
-                LevelEntity,
-                CollisionCooldown::default(),
-            ));
+/// Spawn an atom created by a reaction. Moving products travel at `speed`, or the default speed
+/// if it's `None`.
+fn spawn_product<'a>(
+    commands: &'a mut Commands,
+    (atom_type, direction): Reactant,
+    speed: Option<f32>,
+    cooldown: bool,
+    lineage: Option<Lineage>,
+    position: IVec2,
+    atom_assets: &AtomAssets,
+) -> EntityCommands<'a> {
+    let mut entity = commands.spawn((atom(atom_type, position, atom_assets), LevelEntity));
+    if cooldown {
+        entity.insert(CollisionCooldown::default());
+    }
+    if let Some(direction) = direction {
+        let mut movement = Movement::new(direction);
+        if let Some(speed) = speed {
+            movement.speed = speed;
+        }
+        entity.insert(movement);
+    }
+    if let Some(lineage) = lineage {
+        entity.insert(lineage);
+    }
+    entity
+}
 
-            if let Some(movement) = movement {
-                entity.insert(movement);
-            }
+fn reaction_sound(sound: ReactionSound, audio_assets: &AudioAssets) -> Handle<AudioSource> {
+    match sound {
+        ReactionSound::Merge => audio_assets.merge_sfx.clone(),
+        ReactionSound::Split => audio_assets.split_sfx.clone(),
+        ReactionSound::SplitBig => audio_assets.split_big_sfx.clone(),
+        ReactionSound::Hit => audio_assets.hit_sfx.clone(),
+    }
+}
 
-            commands.spawn(sound_effect(audio_assets.merge_sfx.clone()));
+/// Walls with durability lose a hit point on each impact, and break once they run out
+fn damage_wall(
+    wall_entity: Entity,
+    durability_query: &mut Query<&mut Durability>,
+    commands: &mut Commands,
+) {
+    if let Ok(mut durability) = durability_query.get_mut(wall_entity) {
+        durability.remaining = durability.remaining.saturating_sub(1);
+        if durability.remaining == 0 {
+            commands.entity(wall_entity).despawn();
         }
+    }
+}
 
-        // two splitting atoms split into 3
-        (AtomType::Splitting, AtomType::Splitting) => {
-            commands.entity(entity1).despawn();
-            commands.entity(entity2).despawn();
-
-            commands.spawn(sound_effect(audio_assets.split_sfx.clone()));
-
-            let movement = movement1.cloned().or_else(|| movement2.cloned());
-            match movement {
-                Some(movement) => {
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        Movement::new(movement.direction),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        Movement::new(movement.direction.clockwise().clockwise()),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        Movement::new(movement.direction.anticlockwise().anticlockwise()),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                }
-                None => warn!("Collision between two stationary atoms. This shouldn't happen."),
-            }
-        }
+fn handle_portal_collision(
+    entity: Entity,
+    atom_type: AtomType,
+    movement: &Movement,
+    lineage: Option<&Lineage>,
+    exit_position: IVec2,
+    commands: &mut Commands,
+    atom_assets: &AtomAssets,
+) {
+    // Despawn the atom entering the portal
+    commands.entity(entity).despawn();
+
+    // Spawn it again at the exit, travelling in the same direction
+    let mut entity = commands.spawn((
+        atom(atom_type, exit_position, atom_assets),
+        movement.clone(),
+        LevelEntity,
+        CollisionCooldown::default(),
+    ));
+    if let Some(lineage) = lineage {
+        entity.insert(*lineage);
+    }
+}
 
-        // Reactives combine into antimatter
-        (AtomType::Reactive, AtomType::Reactive) => {
-            commands.entity(entity1).despawn();
-            commands.entity(entity2).despawn();
-
-            commands.spawn(sound_effect(audio_assets.merge_sfx.clone()));
-
-            let movement = movement1.cloned().or_else(|| movement2.cloned());
-            match movement {
-                Some(movement) => {
-                    commands.spawn((
-                        atom(AtomType::Antimatter, event.position, &atom_assets),
-                        Movement::new(movement.direction),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                }
-                None => warn!("Collision between two stationary atoms. This shouldn't happen."),
-            }
-        }
+/// An atom that will decay after a number of ticks
+#[derive(Component)]
+pub struct Unstable {
+    decay: Decay,
+    ticks_remaining: u32,
+}
 
-        // Basic pushes reactive
-        (AtomType::Basic, AtomType::Reactive) | (AtomType::Reactive, AtomType::Basic) => {
-            commands.entity(entity1).despawn();
-            commands.entity(entity2).despawn();
-
-            commands.spawn(sound_effect(audio_assets.hit_sfx.clone()));
-
-            let movement = movement1.cloned().or_else(|| movement2.cloned());
-            match movement {
-                Some(movement) => {
-                    commands.spawn((
-                        atom(AtomType::Reactive, event.position, &atom_assets),
-                        Movement::new(movement.direction),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                }
-                None => warn!("Collision between two stationary atoms. This shouldn't happen."),
-            }
-        }
+#[derive(Component)]
+struct DecayRing;
+
+pub fn unstable(decay: Decay, atom_assets: &AtomAssets) -> impl Bundle {
+    (
+        Unstable {
+            ticks_remaining: decay.ticks,
+            decay,
+        },
+        children![(
+            Name::new("Decay Ring"),
+            Sprite {
+                image: atom_assets.circle.clone(),
+                color: Color::linear_rgba(0.9, 0.3, 0.2, 0.6),
+                ..Default::default()
+            },
+            Transform::from_scale(Vec3::splat(DECAY_RING_SCALE))
+                .with_translation(Vec3::new(7.0, -12.0, 1.0)),
+            RenderLayers::layer(2),
+            DecayRing,
+        )],
+    )
+}
 
-        // Reactive and splitting split into 4
-        (AtomType::Reactive, AtomType::Splitting) | (AtomType::Splitting, AtomType::Reactive) => {
-            commands.entity(entity1).despawn();
-            commands.entity(entity2).despawn();
-
-            commands.spawn(sound_effect(audio_assets.split_big_sfx.clone()));
-
-            let movement = movement1.cloned().or_else(|| movement2.cloned());
-            match movement {
-                Some(movement) => {
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        Movement::new(movement.direction.clockwise()),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        Movement::new(movement.direction.clockwise().clockwise().clockwise()),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        Movement::new(movement.direction.anticlockwise()),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        Movement::new(
-                            movement
-                                .direction
-                                .anticlockwise()
-                                .anticlockwise()
-                                .anticlockwise(),
-                        ),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                }
-                None => warn!("Collision between two stationary atoms. This shouldn't happen."),
-            }
-        }
+/// Scale of the decay ring when the countdown starts. The ring shrinks towards the atom as the
+/// countdown runs out.
+const DECAY_RING_SCALE: f32 = 1.6;
 
-        // Basic and antimatter - similar to splitting but in opposite direction
-        (AtomType::Basic, AtomType::Antimatter) | (AtomType::Antimatter, AtomType::Basic) => {
-            commands.entity(entity1).despawn();
-            commands.entity(entity2).despawn();
-
-            commands.spawn(sound_effect(audio_assets.split_sfx.clone()));
-
-            let movement = movement1.cloned().or_else(|| movement2.cloned());
-
-            match movement {
-                Some(movement) => {
-                    let (dir1, dir2) = get_split_directions(movement.direction.opposite());
-
-                    // Spawn basic atoms at 45 degree angles
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        Movement::new(dir1),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        Movement::new(dir2),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                }
-                None => warn!("Collision between two stationary atoms. This shouldn't happen."),
-            }
+fn decay_unstable_atoms(
+    mut commands: Commands,
+    clock: Res<ExperimentClock>,
+    mut unstable_atoms: Query<(
+        Entity,
+        &mut Unstable,
+        &AtomType,
+        &Transform,
+        Option<&Movement>,
+        Option<&Lineage>,
+    )>,
+    atom_assets: Res<AtomAssets>,
+    audio_assets: Res<AudioAssets>,
+    mut reaction_events: EventWriter<ReactionEvent>,
+) {
+    if !clock.just_ticked() {
+        return;
+    }
+    for (entity, mut unstable, atom_type, transform, movement, lineage) in &mut unstable_atoms {
+        unstable.ticks_remaining = unstable.ticks_remaining.saturating_sub(1);
+        if unstable.ticks_remaining > 0 {
+            continue;
         }
-
-        // Splitting atom collisions - split
-        (AtomType::Splitting, AtomType::Basic) | (AtomType::Basic, AtomType::Splitting) => {
-            commands.entity(entity1).despawn();
-            commands.entity(entity2).despawn();
-
-            commands.spawn(sound_effect(audio_assets.split_sfx.clone()));
-
-            let movement = movement1.cloned().or_else(|| movement2.cloned());
-
-            match movement {
-                Some(movement) => {
-                    let (dir1, dir2) = get_split_directions(movement.direction);
-
-                    // Spawn basic atoms at 45 degree angles
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        Movement::new(dir1),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        Movement::new(dir2),
-                        LevelEntity,
-                        CollisionCooldown::default(),
-                    ));
-                }
-                None => {
-                    warn!("Collision between two stationary atoms. This shouldn't happen.");
-                    commands.spawn((
-                        atom(AtomType::Basic, event.position, &atom_assets),
-                        LevelEntity,
-                    ));
-                }
-            }
+        debug!(
+            "{:?} atom decayed into {:?}",
+            atom_type, unstable.decay.product
+        );
+        let position = IVec2::new(
+            transform.translation.x.round() as i32,
+            transform.translation.y.round() as i32,
+        );
+        // Products are spawned where the atom currently is, which might be between grid positions
+        let product_transform =
+            Transform::from_translation(transform.translation).with_scale(transform.scale);
+
+        commands.entity(entity).despawn();
+
+        let products = unstable
+            .decay
+            .product
+            .products(movement.map(|movement| movement.direction));
+        for &product in &products {
+            spawn_product(
+                &mut commands,
+                product,
+                None,
+                true,
+                lineage.copied(),
+                position,
+                &atom_assets,
+            )
+            .insert(product_transform);
         }
 
-        // Antimatter phases through other atoms
-        (AtomType::Antimatter, _) | (_, AtomType::Antimatter) => {}
+        commands.spawn(sound_effect(reaction_sound(
+            unstable.decay.product.sound(),
+            &audio_assets,
+        )));
+        reaction_events.write(ReactionEvent {
+            tick: clock.tick(),
+            position,
+            inputs: vec![*atom_type],
+            outputs: products.iter().map(|(atom_type, _)| *atom_type).collect(),
+            depth: lineage.map_or(0, |lineage| lineage.depth),
+        });
     }
 }
 
-fn handle_wall_collision(
-    other_entity: Entity,
-    other_type: AtomType,
-    other_movement: Option<&Movement>,
-    position: IVec2,
-    commands: &mut Commands,
-    atom_assets: &AtomAssets,
+fn update_decay_rings(
+    clock: Res<ExperimentClock>,
+    unstable_atoms: Query<&Unstable>,
+    mut rings: Query<(&ChildOf, &mut Transform), With<DecayRing>>,
 ) {
-    match other_movement {
-        Some(movement) => {
-            // Bounce the atom by reversing its direction
-            let mut bounced_movement = movement.clone();
-            bounced_movement.direction = bounced_movement.direction.opposite();
-
-            // Despawn the original atom
-            commands.entity(other_entity).despawn();
-
-            // Spawn bounced atom
-            commands.spawn((
-                atom(other_type, position, atom_assets),
-                bounced_movement,
-                LevelEntity,
-                CollisionCooldown::default(),
-            ));
-        }
-        None => {
-            // Stationary atom hitting wall - just despawn it
-            commands.entity(other_entity).despawn();
+    for (child_of, mut transform) in &mut rings {
+        if let Ok(unstable) = unstable_atoms.get(child_of.parent()) {
+            let remaining = (unstable.ticks_remaining as f32 - clock.tick_fraction()).max(0.0)
+                / unstable.decay.ticks.max(1) as f32;
+            transform.scale = Vec3::splat(1.0 + (DECAY_RING_SCALE - 1.0) * remaining);
         }
     }
 }
 
-/// Get the two directions for splitting at 45 degree angles
-fn get_split_directions(direction: CardinalDirection) -> (CardinalDirection, CardinalDirection) {
-    (direction.clockwise(), direction.anticlockwise())
-}
-
 /// Used to prevent newly spawned atoms from immediately colliding again
 #[derive(Component)]
 pub struct CollisionCooldown(Timer);
@@ -380,16 +483,32 @@ fn tick_collision_cooldown(
 
 fn detect_atom_collisions(
     moving_atoms_query: Query<
-        (Entity, &Transform, &Movement),
-        (With<AtomType>, Without<CollisionCooldown>),
+        (Entity, &Transform, &SpawnOrder),
+        (With<AtomType>, With<Movement>, Without<CollisionCooldown>),
+    >,
+    stationary_atoms_query: Query<
+        (Entity, &Transform, &SpawnOrder),
+        (With<AtomType>, Without<Movement>),
     >,
-    stationary_atoms_query: Query<(Entity, &Transform), (With<AtomType>, Without<Movement>)>,
     mut commands: Commands,
 ) {
     let epsilon = 0.05;
-    let mut potentially_colliding_positions: HashMap<IVec2, Vec<Entity>> = HashMap::new();
+    // Atoms are found in the order they entered the experiment, and collisions are handled in the
+    // order they're found, so that results are the same as in the simulation
+    let mut moving_atoms: Vec<_> = moving_atoms_query.iter().collect();
+    moving_atoms.sort_by_key(|(_, _, order)| **order);
+    let mut potentially_colliding_positions: Vec<(IVec2, Vec<Entity>)> = Vec::new();
+    let mut collision_indices: HashMap<IVec2, usize> = HashMap::new();
+    let mut add_to_collision = |position: IVec2, entity: Entity| {
+        let collision = *collision_indices.entry(position).or_insert_with(|| {
+            potentially_colliding_positions.push((position, Vec::new()));
+            potentially_colliding_positions.len() - 1
+        });
+        potentially_colliding_positions[collision].1.push(entity);
+    };
+    let mut any_at_intersection = false;
 
-    for (moving_entity, moving_transform, _) in moving_atoms_query.iter() {
+    for (moving_entity, moving_transform, _) in moving_atoms {
         // check if we're at a grid intersection (only time that collisions should occur)
         let pos = moving_transform.translation;
         let nearest_x = pos.x.round();
@@ -398,25 +517,28 @@ fn detect_atom_collisions(
         let dy = (pos.y - nearest_y).abs();
 
         if dx <= epsilon && dy <= epsilon {
-            potentially_colliding_positions
-                .entry(IVec2::new(nearest_x as i32, nearest_y as i32))
-                .or_default()
-                .push(moving_entity);
+            add_to_collision(
+                IVec2::new(nearest_x as i32, nearest_y as i32),
+                moving_entity,
+            );
+            any_at_intersection = true;
         }
     }
 
-    if !potentially_colliding_positions.is_empty() {
-        for (entity, transform) in stationary_atoms_query.iter() {
+    if any_at_intersection {
+        let mut stationary_atoms: Vec<_> = stationary_atoms_query.iter().collect();
+        stationary_atoms.sort_by_key(|(_, _, order)| **order);
+        for (entity, transform, _) in stationary_atoms {
             let pos = transform.translation;
             // It's assumed that stationary atoms are already at grid intersections
-            potentially_colliding_positions
-                .entry(IVec2::new(pos.x.round() as i32, pos.y.round() as i32))
-                .or_default()
-                .push(entity);
+            add_to_collision(
+                IVec2::new(pos.x.round() as i32, pos.y.round() as i32),
+                entity,
+            );
         }
     }
 
-    for (pos, entities) in potentially_colliding_positions.into_iter() {
+    for (pos, entities) in potentially_colliding_positions {
         if entities.len() > 1 {
             debug!("{} entities collided at {pos}", entities.len());
             commands.trigger(CollisionEvent {
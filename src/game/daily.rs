@@ -0,0 +1,133 @@
+//! The daily challenge: a generated puzzle that's the same for everyone on the same day, without
+//! needing a server. Days are counted in UTC so that everyone agrees on when a new one starts.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::save::SaveData;
+
+use super::{
+    generator::{Difficulty, generate_level},
+    leaderboard::is_replaying,
+    level::{CurrentLevel, Level},
+    state::GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(GameState::LevelComplete),
+        record_daily_completion.run_if(resource_exists::<DailyChallenge>.and(not(is_replaying))),
+    );
+}
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Progress on daily challenges, kept in the save file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DailyProgress {
+    /// The most recent day whose challenge was completed, counted from the Unix epoch
+    #[serde(default)]
+    pub last_completed: Option<u32>,
+    /// Challenges completed on consecutive days, ending with `last_completed`
+    #[serde(default)]
+    pub streak: u32,
+    #[serde(default)]
+    pub best_streak: u32,
+}
+
+impl DailyProgress {
+    pub fn is_completed(&self, day: u32) -> bool {
+        self.last_completed == Some(day)
+    }
+
+    /// The streak as of the given day. Missing a day's challenge ends the streak, but today's
+    /// challenge can still be completed to continue yesterday's.
+    pub fn streak(&self, day: u32) -> u32 {
+        match self.last_completed {
+            Some(last) if last + 1 >= day => self.streak,
+            _ => 0,
+        }
+    }
+
+    pub fn complete(&mut self, day: u32) {
+        match self.last_completed {
+            Some(last) if last >= day => return,
+            Some(last) if last + 1 == day => self.streak += 1,
+            _ => self.streak = 1,
+        }
+        self.last_completed = Some(day);
+        self.best_streak = self.best_streak.max(self.streak);
+    }
+}
+
+/// The daily challenge being played
+#[derive(Resource)]
+pub struct DailyChallenge {
+    pub day: u32,
+    pub level: Handle<Level>,
+}
+
+/// Today's date in UTC, as days since the Unix epoch
+pub fn today() -> u32 {
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    (since_epoch.as_secs() / SECS_PER_DAY) as u32
+}
+
+/// A day as a date, e.g. "2025-06-01"
+pub fn format_day(day: u32) -> String {
+    // Converts days to a date in the proleptic Gregorian calendar, which repeats every 400 years
+    // (146,097 days). See <https://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+    let days = i64::from(day) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    // Months are counted from March, so that the leap day is at the end of the year
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year}-{month:02}-{day_of_month:02}")
+}
+
+/// Challenges get harder through the week, with the hardest at the weekend
+pub fn daily_difficulty(day: u32) -> Difficulty {
+    // The Unix epoch was a Thursday
+    match (day + 3) % 7 {
+        0 | 1 => Difficulty::Easy,
+        2..=4 => Difficulty::Medium,
+        _ => Difficulty::Hard,
+    }
+}
+
+/// The puzzle for the given day, generated with the day as the seed
+pub fn daily_level(day: u32) -> Option<Level> {
+    let mut level = generate_level(u64::from(day), daily_difficulty(day))?;
+    level.sidebar_text = format!(
+        "Daily challenge for {}.\n{}",
+        format_day(day),
+        level.sidebar_text
+    );
+    Some(level)
+}
+
+fn record_daily_completion(
+    daily: Res<DailyChallenge>,
+    current_level: Res<CurrentLevel>,
+    mut save_data: ResMut<SaveData>,
+) {
+    if let CurrentLevel::Custom(level_handle) = &*current_level
+        && *level_handle == daily.level
+    {
+        save_data.daily.complete(daily.day);
+        save_data.save();
+    }
+}
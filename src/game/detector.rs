@@ -0,0 +1,152 @@
+use bevy::{platform::collections::HashSet, prelude::*, render::view::RenderLayers};
+
+use crate::{
+    AppSystems, PausableSystems,
+    audio::{AudioAssets, sound_effect},
+    screens::Screen,
+    theme::palette::{LABEL_TEXT, OFF_WHITE},
+};
+
+use super::{
+    atom::{AtomAssets, AtomType},
+    level::LevelDetector,
+    movement::{CardinalDirection, Movement},
+    placement::GridPos,
+    state::{ExperimentSystems, GameState},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        count_passing_atoms
+            .run_if(in_state(GameState::Running))
+            .in_set(AppSystems::Update)
+            .in_set(ExperimentSystems::Detect)
+            .in_set(PausableSystems),
+    );
+    app.add_systems(Update, update_detector_labels.in_set(AppSystems::Update));
+}
+
+/// A stationary cell that counts the atoms passing through it without consuming them.
+#[derive(Component)]
+pub struct Detector {
+    pub atom_type: AtomType,
+    /// Only atoms travelling in this direction are counted
+    pub direction: Option<CardinalDirection>,
+    /// Number of atoms required for the detector to be satisfied
+    pub target: Option<u32>,
+    pub count: u32,
+    /// Atoms that have already been counted, so that an atom is only counted once per pass
+    counted: HashSet<Entity>,
+}
+
+impl Detector {
+    pub fn is_satisfied(&self) -> bool {
+        self.target.is_none_or(|target| self.count >= target)
+    }
+
+    fn label(&self) -> String {
+        match self.target {
+            Some(target) => format!("{}/{}", self.count, target),
+            None => self.count.to_string(),
+        }
+    }
+}
+
+#[derive(Component)]
+struct DetectorLabel;
+
+pub fn detector(level_detector: &LevelDetector, atom_assets: &AtomAssets) -> impl Bundle {
+    let position = level_detector.position;
+    let detector = Detector {
+        atom_type: level_detector.atom_type,
+        direction: level_detector.direction,
+        target: level_detector.target,
+        count: 0,
+        counted: HashSet::new(),
+    };
+    let label = detector.label();
+    (
+        Name::new("Detector"),
+        detector,
+        GridPos(position),
+        Transform::from_xyz(position.x as f32, position.y as f32, 0.0),
+        Visibility::default(),
+        StateScoped(Screen::Gameplay),
+        children![
+            (
+                level_detector.atom_type.get_ghost_sprite(atom_assets),
+                Transform::from_scale(Vec3::splat(0.002)),
+                RenderLayers::layer(2),
+            ),
+            (
+                Sprite {
+                    image: atom_assets.circle.clone(),
+                    color: Color::linear_rgba(0.2, 0.8, 0.4, 0.25),
+                    custom_size: Some(Vec2::splat(0.9)),
+                    ..Default::default()
+                },
+                Transform::from_xyz(0.0, 0.0, 1.0),
+                RenderLayers::layer(2),
+            ),
+            (
+                Name::new("Detector Label"),
+                Text2d::new(label),
+                TextFont::from_font_size(32.0),
+                TextColor(OFF_WHITE),
+                Transform::from_xyz(0.0, 0.5, 2.0).with_scale(Vec3::splat(0.01)),
+                RenderLayers::layer(2),
+                DetectorLabel,
+            )
+        ],
+    )
+}
+
+fn count_passing_atoms(
+    mut commands: Commands,
+    mut detectors: Query<(&Transform, &mut Detector)>,
+    // Only atoms passing through are counted, not ones sitting on the detector
+    atoms: Query<(Entity, &Transform, &AtomType, &Movement)>,
+    audio_assets: Res<AudioAssets>,
+) {
+    for (detector_transform, mut detector) in &mut detectors {
+        for (atom_entity, atom_transform, atom_type, movement) in &atoms {
+            if *atom_type != detector.atom_type || detector.counted.contains(&atom_entity) {
+                continue;
+            }
+            if detector
+                .direction
+                .is_some_and(|direction| movement.direction != direction)
+            {
+                continue;
+            }
+            let distance = detector_transform
+                .translation
+                .distance(atom_transform.translation);
+            let detection_threshold = 0.2;
+
+            if distance < detection_threshold {
+                detector.counted.insert(atom_entity);
+                detector.count += 1;
+
+                commands.spawn(sound_effect(audio_assets.hit_sfx.clone()));
+            }
+        }
+    }
+}
+
+fn update_detector_labels(
+    detectors: Query<&Detector, Changed<Detector>>,
+    mut labels: Query<(&ChildOf, &mut Text2d, &mut TextColor), With<DetectorLabel>>,
+) {
+    for (child_of, mut text, mut color) in &mut labels {
+        if let Ok(detector) = detectors.get(child_of.parent()) {
+            text.0 = detector.label();
+            color.0 = if detector.target.is_some() && detector.is_satisfied() {
+                LABEL_TEXT
+            } else {
+                OFF_WHITE
+            };
+        }
+    }
+}
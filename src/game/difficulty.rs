@@ -0,0 +1,216 @@
+//! Estimates how hard a level is from its solution and how many ways there are to go wrong.
+//!
+//! Estimates are used to badge the campaign levels in level select, to warn about levels that are
+//! much harder than the one before, and to sort level files from the command line.
+
+use bevy::{platform::collections::HashSet, prelude::*};
+
+use crate::LEVELS;
+
+use super::{
+    analysis::ReactionKind,
+    level::{Level, LevelAssets, LevelPlacement},
+    simulation::simulate,
+    solver::{SolveResult, SolverLimits, level_positions, solve},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        estimate_campaign_difficulty.run_if(resource_added::<LevelAssets>),
+    );
+}
+
+/// How much each property adds to the difficulty score
+const SEARCH_SPACE_WEIGHT: f32 = 0.5;
+const ATOM_WEIGHT: f32 = 1.0;
+const REACTION_TYPE_WEIGHT: f32 = 0.5;
+const CHAIN_DEPTH_WEIGHT: f32 = 0.5;
+
+/// Lowest score for each rating above the first
+const RATING_THRESHOLDS: [f32; 4] = [5.0, 9.0, 13.0, 20.0];
+
+const RATING_NAMES: [&str; 5] = ["Easy", "Moderate", "Tricky", "Hard", "Fiendish"];
+
+/// The properties of a level that make it hard
+#[derive(Debug, Clone)]
+pub struct DifficultyEstimate {
+    /// How many layouts of `min_atoms` atoms the player could try, as a power of ten
+    pub search_space: f32,
+    /// Fewest atoms known to complete the level
+    pub min_atoms: usize,
+    /// Different reactions the solution relies on
+    pub reaction_types: usize,
+    /// Longest chain of reactions in the solution
+    pub chain_depth: u32,
+}
+
+impl DifficultyEstimate {
+    pub fn score(&self) -> f32 {
+        SEARCH_SPACE_WEIGHT * self.search_space
+            + ATOM_WEIGHT * self.min_atoms as f32
+            + REACTION_TYPE_WEIGHT * self.reaction_types as f32
+            + CHAIN_DEPTH_WEIGHT * self.chain_depth as f32
+    }
+
+    /// Difficulty from 1 to 5
+    pub fn rating(&self) -> u8 {
+        let score = self.score();
+        1 + RATING_THRESHOLDS
+            .iter()
+            .filter(|&&threshold| score >= threshold)
+            .count() as u8
+    }
+
+    /// The rating as a word, e.g. "Tricky"
+    pub fn rating_name(&self) -> &'static str {
+        RATING_NAMES[self.rating() as usize - 1]
+    }
+
+    /// e.g. "Tricky (10.4): 10^7.7 layouts, 4 atoms, 1 reaction type, chain depth 4"
+    pub fn describe(&self) -> String {
+        format!(
+            "{} ({:.1}): 10^{:.1} layouts, {} atom{}, {} reaction type{}, chain depth {}",
+            self.rating_name(),
+            self.score(),
+            self.search_space,
+            self.min_atoms,
+            if self.min_atoms == 1 { "" } else { "s" },
+            self.reaction_types,
+            if self.reaction_types == 1 { "" } else { "s" },
+            self.chain_depth
+        )
+    }
+}
+
+/// Estimate a level's difficulty from its shortest reference solution, or the atom par if that's
+/// lower. Levels without a reference solution are solved first, which can take a while.
+pub fn estimate_difficulty(level: &Level) -> DifficultyEstimate {
+    let limits = SolverLimits::for_level(level);
+    let solution = shortest_solution(level).or_else(|| match solve(level, &limits).result {
+        SolveResult::Solved { solution, .. } => Some(solution),
+        _ => None,
+    });
+    estimate_from_solution(level, &limits, solution.as_deref())
+}
+
+/// Estimate a level's difficulty from its shortest reference solution only, without solving it.
+/// Returns `None` for levels without a reference solution.
+pub fn estimate_difficulty_from_solutions(level: &Level) -> Option<DifficultyEstimate> {
+    let solution = shortest_solution(level)?;
+    Some(estimate_from_solution(
+        level,
+        &SolverLimits::for_level(level),
+        Some(&solution),
+    ))
+}
+
+fn shortest_solution(level: &Level) -> Option<Vec<LevelPlacement>> {
+    level
+        .solutions
+        .iter()
+        .min_by_key(|solution| solution.len())
+        .cloned()
+}
+
+fn estimate_from_solution(
+    level: &Level,
+    limits: &SolverLimits,
+    solution: Option<&[LevelPlacement]>,
+) -> DifficultyEstimate {
+    let (min_atoms, reaction_types, chain_depth) = match solution {
+        Some(solution) => {
+            let report = simulate(level, &LevelPlacement::layout(solution));
+            let kinds: HashSet<_> = report.reactions.iter().map(ReactionKind::of).collect();
+            let depth = report
+                .reactions
+                .iter()
+                .map(|reaction| reaction.depth)
+                .max()
+                .unwrap_or(0);
+            let atoms = level
+                .par
+                .atoms
+                .map_or(solution.len(), |par| solution.len().min(par as usize));
+            (atoms, kinds.len(), depth)
+        }
+        // Without a solution, assume the worst the solver would try
+        None => (limits.max_atoms, 0, 0),
+    };
+
+    DifficultyEstimate {
+        search_space: search_space(level, limits, min_atoms),
+        min_atoms,
+        reaction_types,
+        chain_depth,
+    }
+}
+
+/// The number of ways to place `atoms` atoms in the free cells of the solver's region, as a power
+/// of ten
+fn search_space(level: &Level, limits: &SolverLimits, atoms: usize) -> f32 {
+    let occupied: HashSet<IVec2> = level_positions(level).into_iter().collect();
+    let region = limits.region;
+    let free_cells = ((region.width() + 1) * (region.height() + 1)) as usize
+        - occupied
+            .iter()
+            .filter(|position| region.contains(**position))
+            .count();
+    let directions = if level.allow_moving_placement { 9 } else { 1 };
+    let choices_per_cell = (level.placeable_atoms.len() * directions) as f32;
+    // Choosing `atoms` cells out of `free_cells`, and what to put in each
+    (0..atoms)
+        .map(|index| {
+            ((free_cells.saturating_sub(index)) as f32 * choices_per_cell / (index + 1) as f32)
+                .max(1.0)
+                .log10()
+        })
+        .sum()
+}
+
+/// Indices of levels rated at least two steps harder than the level before them. Levels without
+/// an estimate are never counted as spikes.
+pub fn difficulty_spikes(estimates: &[Option<DifficultyEstimate>]) -> Vec<usize> {
+    estimates
+        .windows(2)
+        .enumerate()
+        .filter(|(_, pair)| match pair {
+            [Some(previous), Some(next)] => next.rating() > previous.rating() + 1,
+            _ => false,
+        })
+        .map(|(index, _)| index + 1)
+        .collect()
+}
+
+/// Difficulty of each campaign level, by level index. Levels without a reference solution have no
+/// estimate, since solving them would stall the game.
+#[derive(Resource)]
+pub struct LevelDifficulties(pub Vec<Option<DifficultyEstimate>>);
+
+fn estimate_campaign_difficulty(
+    mut commands: Commands,
+    level_handles: Res<LevelAssets>,
+    level_assets: Res<Assets<Level>>,
+) {
+    let estimates: Vec<_> = level_handles
+        .levels
+        .iter()
+        .map(|handle| {
+            level_assets
+                .get(handle)
+                .and_then(estimate_difficulty_from_solutions)
+        })
+        .collect();
+    for index in difficulty_spikes(&estimates) {
+        if let (Some(previous), Some(next)) = (&estimates[index - 1], &estimates[index]) {
+            warn!(
+                "{} is much harder than {}: {} after {}",
+                LEVELS[index],
+                LEVELS[index - 1],
+                next.describe(),
+                previous.describe()
+            );
+        }
+    }
+    commands.insert_resource(LevelDifficulties(estimates));
+}
@@ -0,0 +1,120 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, render::view::RenderLayers};
+use bevy_easings::Ease;
+
+use crate::{AppSystems, PausableSystems, screens::Screen};
+
+use super::{
+    atom::{AtomAssets, AtomType, atom},
+    collision::CollisionCooldown,
+    level::{LevelEmitter, LevelEntity},
+    movement::{CardinalDirection, Movement},
+    placement::GridPos,
+    state::{ExperimentSystems, GameState},
+    tick::ExperimentClock,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        emit_atoms
+            .run_if(in_state(GameState::Running))
+            .in_set(AppSystems::Update)
+            .in_set(ExperimentSystems::Emit)
+            .in_set(PausableSystems),
+    );
+    app.add_systems(
+        Update,
+        draw_emitter_arrows.run_if(in_state(GameState::Placement)),
+    );
+}
+
+/// A stationary cell that launches atoms on a fixed schedule.
+#[derive(Component)]
+pub struct Emitter {
+    pub atom_type: AtomType,
+    pub direction: CardinalDirection,
+    /// Number of ticks between emissions.
+    pub interval: u32,
+    /// Number of atoms left to emit, or `None` if the emitter never runs out.
+    pub remaining: Option<u32>,
+}
+
+pub fn emitter(level_emitter: &LevelEmitter, atom_assets: &AtomAssets) -> impl Bundle {
+    let position = level_emitter.position;
+    (
+        Name::new("Emitter"),
+        level_emitter.atom_type.get_ghost_sprite(atom_assets),
+        Emitter {
+            atom_type: level_emitter.atom_type,
+            direction: level_emitter.direction,
+            interval: level_emitter.interval,
+            remaining: level_emitter.count,
+        },
+        GridPos(position),
+        Transform::from_xyz(position.x as f32, position.y as f32, 0.0)
+            .with_scale(Vec3::splat(0.002)),
+        RenderLayers::layer(2),
+        StateScoped(Screen::Gameplay),
+        children![(
+            (Sprite {
+                image: atom_assets.circle.clone(),
+                color: Color::linear_rgba(0.4, 0.4, 0.8, 0.3),
+                ..Default::default()
+            }),
+            Transform::from_scale(Vec3::splat(1.3))
+                .with_translation(Vec3::new(7.0, -12.0, 1.0))
+                .ease_to(
+                    Transform::from_scale(Vec3::splat(1.5))
+                        .with_translation(Vec3::new(7.0, -12.0, 1.0)),
+                    bevy_easings::EaseFunction::SineInOut,
+                    bevy_easings::EasingType::PingPong {
+                        duration: Duration::from_millis(1000),
+                        pause: None
+                    }
+                ),
+            RenderLayers::layer(2),
+        )],
+    )
+}
+
+fn emit_atoms(
+    mut commands: Commands,
+    clock: Res<ExperimentClock>,
+    mut emitters: Query<(&mut Emitter, &GridPos)>,
+    atom_assets: Res<AtomAssets>,
+) {
+    if !clock.just_ticked() {
+        return;
+    }
+    for (mut emitter, grid_pos) in &mut emitters {
+        if emitter.interval == 0 || !clock.tick().is_multiple_of(emitter.interval) {
+            continue;
+        }
+        if let Some(remaining) = emitter.remaining.as_mut() {
+            if *remaining == 0 {
+                continue;
+            }
+            *remaining -= 1;
+        }
+        commands.spawn((
+            atom(emitter.atom_type, grid_pos.0, &atom_assets),
+            Movement::new(emitter.direction),
+            LevelEntity,
+            CollisionCooldown::default(),
+        ));
+    }
+}
+
+fn draw_emitter_arrows(mut gizmos: Gizmos, emitters: Query<(&Emitter, &GridPos)>) {
+    for (emitter, grid_pos) in &emitters {
+        let direction = emitter.direction.as_velocity();
+        let position = grid_pos.0.as_vec2();
+        gizmos.arrow_2d(
+            position + (direction * 0.3),
+            position + (direction * 1.2),
+            LinearRgba::rgb(0.8, 0.4, 0.8),
+        );
+    }
+}
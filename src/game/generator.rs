@@ -0,0 +1,393 @@
+//! Generates new levels from a seed. Walls and atoms are laid out at random, atoms are placed
+//! where they cause reactions, and then something that only happens because of those atoms
+//! becomes the goal.
+//!
+//! The placed atoms are kept as the level's reference solution, so every generated level can be
+//! completed.
+
+use bevy::{platform::collections::HashSet, prelude::*};
+use rand::{Rng, SeedableRng, seq::SliceRandom};
+use rand_chacha::ChaCha8Rng;
+
+use super::{
+    atom::AtomType,
+    level::{Level, LevelAtom, LevelGoal, LevelGoalPosition, LevelPar, LevelPlacement},
+    movement::CardinalDirection,
+    simulation::{DEFAULT_TIME_LIMIT_SECS, STEP_SECS, Simulation, simulate},
+};
+
+/// Cells that generated levels are laid out in, leaving a margin inside the visible area
+const GENERATED_REGION: IRect = IRect {
+    min: IVec2::new(-6, -3),
+    max: IVec2::new(6, 3),
+};
+
+/// Layouts to try before giving up on a seed
+const MAX_ATTEMPTS: usize = 200;
+
+/// Random cells to try when looking for a free one
+const CELL_TRIES: usize = 20;
+
+/// Cells to try for each placed atom, looking for one that causes a new reaction
+const PLACEMENT_TRIES: usize = 10;
+
+/// How close a moving atom must pass to a grid intersection to count as reaching it
+const REACH_THRESHOLD: f32 = 0.1;
+
+/// Chance of making the goal creating a new type of atom, when that's possible
+const CREATE_ATOM_CHANCE: f64 = 0.3;
+
+/// Leeway on the tick par, matching the campaign levels
+const PAR_TICK_MARGIN: u32 = 2;
+
+const MOVING_ATOM_TYPES: [AtomType; 3] = [AtomType::Basic, AtomType::Basic, AtomType::Splitting];
+
+const STATIONARY_ATOM_TYPES: [AtomType; 3] =
+    [AtomType::Basic, AtomType::Splitting, AtomType::Reactive];
+
+/// How much of everything a generated level contains
+#[derive(Debug, Clone)]
+pub struct GeneratorParams {
+    /// Atoms that are moving when the experiment starts
+    pub moving_atoms: usize,
+    pub walls: usize,
+    /// Stationary atoms, other than walls, that are part of the level
+    pub stationary_atoms: usize,
+    /// Atoms in the reference solution
+    pub placed_atoms: usize,
+    /// Most goal positions for levels where atoms must reach positions
+    pub goal_positions: u32,
+    /// Atoms the player may place
+    pub placeable_atoms: Vec<AtomType>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard];
+
+    pub fn params(self) -> GeneratorParams {
+        match self {
+            Difficulty::Easy => GeneratorParams {
+                moving_atoms: 1,
+                walls: 2,
+                stationary_atoms: 0,
+                placed_atoms: 1,
+                goal_positions: 1,
+                placeable_atoms: vec![AtomType::Basic, AtomType::Splitting],
+            },
+            Difficulty::Medium => GeneratorParams {
+                moving_atoms: 1,
+                walls: 4,
+                stationary_atoms: 1,
+                placed_atoms: 2,
+                goal_positions: 2,
+                placeable_atoms: vec![AtomType::Basic, AtomType::Splitting, AtomType::Reactive],
+            },
+            Difficulty::Hard => GeneratorParams {
+                moving_atoms: 2,
+                walls: 6,
+                stationary_atoms: 2,
+                placed_atoms: 3,
+                goal_positions: 3,
+                placeable_atoms: vec![
+                    AtomType::Basic,
+                    AtomType::Splitting,
+                    AtomType::Reactive,
+                    AtomType::Antimatter,
+                ],
+            },
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    /// The difficulty with the given name, e.g. "medium"
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.name().eq_ignore_ascii_case(name))
+    }
+}
+
+/// Generate a level from a seed. The same seed and difficulty always give the same level.
+/// Returns `None` in the unlikely case that no layout for the seed produced a puzzle.
+pub fn generate_level(seed: u64, difficulty: Difficulty) -> Option<Level> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let params = difficulty.params();
+    let mut level = (0..MAX_ATTEMPTS).find_map(|_| try_generate(&mut rng, &params))?;
+    level.sidebar_text = format!(
+        "A generated {} puzzle (seed {seed}).\n{}",
+        difficulty.name(),
+        level.sidebar_text
+    );
+    Some(level)
+}
+
+fn try_generate(rng: &mut ChaCha8Rng, params: &GeneratorParams) -> Option<Level> {
+    let mut level = Level {
+        placeable_atoms: params.placeable_atoms.clone(),
+        level_complete_text: "Puzzle solved!".to_string(),
+        ..Default::default()
+    };
+    let mut occupied = HashSet::new();
+    for _ in 0..params.moving_atoms {
+        let position = free_cell(rng, &occupied)?;
+        occupied.insert(position);
+        level.atoms.push(LevelAtom::new_with_velocity(
+            *MOVING_ATOM_TYPES.choose(rng)?,
+            position,
+            *CardinalDirection::ALL.choose(rng)?,
+        ));
+    }
+    for _ in 0..params.walls {
+        let position = free_cell(rng, &occupied)?;
+        occupied.insert(position);
+        level.atoms.push(LevelAtom::new(AtomType::Wall, position));
+    }
+    for _ in 0..params.stationary_atoms {
+        let position = free_cell(rng, &occupied)?;
+        occupied.insert(position);
+        level.atoms.push(LevelAtom::new(
+            *STATIONARY_ATOM_TYPES.choose(rng)?,
+            position,
+        ));
+    }
+
+    // Place atoms one at a time where the experiment so far passes, keeping each one that causes
+    // another reaction
+    let mut solution: Vec<LevelPlacement> = Vec::new();
+    let mut report = simulate(&level, &[]);
+    for _ in 0..params.placed_atoms {
+        let mut candidates: Vec<_> = report
+            .visited
+            .iter()
+            .copied()
+            .filter(|&cell| GENERATED_REGION.contains(cell) && !occupied.contains(&cell))
+            .collect();
+        // Sort first so the shuffle doesn't depend on the set's iteration order
+        candidates.sort_by_key(|cell| (cell.x, cell.y));
+        candidates.shuffle(rng);
+        let mut placed = None;
+        for position in candidates.into_iter().take(PLACEMENT_TRIES) {
+            let mut placements = solution.clone();
+            placements.push(LevelPlacement {
+                atom_type: *params.placeable_atoms.choose(rng)?,
+                position,
+                direction: None,
+            });
+            let next_report = simulate(&level, &LevelPlacement::layout(&placements));
+            if next_report.reactions.len() > report.reactions.len() {
+                placed = Some((placements, next_report));
+                break;
+            }
+        }
+        let (placements, next_report) = placed?;
+        occupied.insert(placements.last()?.position);
+        solution = placements;
+        report = next_report;
+    }
+
+    level.goal = choose_goal(rng, params, &level, &solution, &occupied)?;
+    level.sidebar_text = match &level.goal {
+        LevelGoal::CreateAtom(atom_type) => {
+            let name = format!("{atom_type:?}").to_lowercase();
+            let article = if name.starts_with(['a', 'e', 'i', 'o', 'u']) {
+                "an"
+            } else {
+                "a"
+            };
+            format!("Create {article} {name} atom.")
+        }
+        _ => "Get the atoms to the highlighted goal positions.".to_string(),
+    };
+
+    // The goal must be reachable with the solution, but not without placing anything, and every
+    // placed atom must be needed
+    let layout = LevelPlacement::layout(&solution);
+    let report = simulate(&level, &layout);
+    if !report.is_complete() || simulate(&level, &[]).is_complete() {
+        return None;
+    }
+    for index in 0..layout.len() {
+        let mut without = layout.clone();
+        without.remove(index);
+        if simulate(&level, &without).is_complete() {
+            return None;
+        }
+    }
+
+    level.par = LevelPar {
+        atoms: Some(solution.len() as u32),
+        ticks: Some(report.ticks + PAR_TICK_MARGIN),
+        reactions: Some(report.reactions.len() as u32),
+        hints: None,
+    };
+    level.solutions = vec![solution];
+    Some(level)
+}
+
+/// A random empty cell in the generated region
+fn free_cell(rng: &mut ChaCha8Rng, occupied: &HashSet<IVec2>) -> Option<IVec2> {
+    (0..CELL_TRIES)
+        .map(|_| {
+            IVec2::new(
+                rng.gen_range(GENERATED_REGION.min.x..=GENERATED_REGION.max.x),
+                rng.gen_range(GENERATED_REGION.min.y..=GENERATED_REGION.max.y),
+            )
+        })
+        .find(|cell| !occupied.contains(cell))
+}
+
+/// Pick a goal from what happens with the solution placed that doesn't happen without it
+fn choose_goal(
+    rng: &mut ChaCha8Rng,
+    params: &GeneratorParams,
+    level: &Level,
+    solution: &[LevelPlacement],
+    occupied: &HashSet<IVec2>,
+) -> Option<LevelGoal> {
+    let before = observe(level, &[]);
+    let after = observe(level, solution);
+
+    // New types of atom make for a goal, as long as the player can't simply place one
+    let mut new_types: Vec<_> = after
+        .atom_types
+        .difference(&before.atom_types)
+        .copied()
+        .filter(|atom_type| {
+            *atom_type != AtomType::Wall && !params.placeable_atoms.contains(atom_type)
+        })
+        .collect();
+    new_types.sort();
+    if !new_types.is_empty() && rng.gen_bool(CREATE_ATOM_CHANCE) {
+        return Some(LevelGoal::CreateAtom(*new_types.choose(rng)?));
+    }
+
+    // Otherwise atoms must reach places they only reach with the solution. Later steps in the
+    // chain reaction make for more interesting goals.
+    let mut candidates: Vec<_> = after
+        .reached
+        .iter()
+        .filter(|reached| {
+            reached.depth > 0
+                && GENERATED_REGION.contains(reached.position)
+                && !occupied.contains(&reached.position)
+                && !before
+                    .reached_cells
+                    .contains(&(reached.atom_type, reached.position))
+        })
+        .collect();
+    candidates.shuffle(rng);
+    candidates.sort_by_key(|reached| std::cmp::Reverse(reached.depth));
+    let count = rng.gen_range(1..=params.goal_positions) as usize;
+    let mut goals: Vec<LevelGoalPosition> = Vec::new();
+    for reached in candidates {
+        if goals.len() == count {
+            break;
+        }
+        if goals.iter().all(|goal| goal.position != reached.position) {
+            goals.push(LevelGoalPosition::new(reached.atom_type, reached.position));
+        }
+    }
+    (!goals.is_empty()).then_some(LevelGoal::ReachPositions(goals))
+}
+
+/// A grid intersection that a moving atom passed through
+struct Reached {
+    atom_type: AtomType,
+    position: IVec2,
+    depth: u32,
+}
+
+/// Everything that happened in an experiment that a goal could be made from
+#[derive(Default)]
+struct Observation {
+    /// Cells reached by each type of atom, in the order they were first reached
+    reached: Vec<Reached>,
+    reached_cells: HashSet<(AtomType, IVec2)>,
+    /// Types of atom that existed at any point
+    atom_types: HashSet<AtomType>,
+}
+
+fn observe(level: &Level, placements: &[LevelPlacement]) -> Observation {
+    let mut simulation = Simulation::new(level, &LevelPlacement::layout(placements));
+    let mut observation = Observation::default();
+    let mut time = 0.0;
+    loop {
+        for atom in simulation.atoms() {
+            observation.atom_types.insert(atom.atom_type);
+            let cell = atom.position.round().as_ivec2();
+            if atom.direction.is_some()
+                && atom.position.distance(cell.as_vec2()) < REACH_THRESHOLD
+                && observation.reached_cells.insert((atom.atom_type, cell))
+            {
+                observation.reached.push(Reached {
+                    atom_type: atom.atom_type,
+                    position: cell,
+                    depth: atom.depth().unwrap_or(0),
+                });
+            }
+        }
+        if simulation.step().is_some() || time >= DEFAULT_TIME_LIMIT_SECS {
+            break;
+        }
+        time += STEP_SECS;
+    }
+    observation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::text_level::level_to_text;
+
+    /// Shared seeds must give the same puzzle everywhere, so this breaks if a change to the
+    /// generator or its random numbers would change the level a seed gives
+    #[test]
+    fn seeds_always_generate_the_same_level() {
+        let level = generate_level(42, Difficulty::Easy).expect("Seed 42 should generate a level");
+        let text = level_to_text(&level).unwrap();
+        assert_eq!(
+            text,
+            r#"(
+    sidebar_text: "A generated easy puzzle (seed 42).\nGet the atoms to the highlighted goal positions.",
+    level_complete_text: "Puzzle solved!",
+    placeable_atoms: [
+        Basic,
+        Splitting,
+    ],
+    par: (
+        atoms: Some(1),
+        ticks: Some(7),
+        reactions: Some(1),
+    ),
+    solutions: [
+        [(atom_type: Splitting, position: (5, 1))],
+    ],
+)
+map: (1, 3) to (7, -4)
+. . . . . . .
+. . . . . B↙.
+. . . . . . .
+. . . . . . .
+. . # . . . .
+. # . . . . .
+. . . . oB. .
+. . . . . . .
+"#,
+            "\n{text}"
+        );
+    }
+}
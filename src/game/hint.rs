@@ -0,0 +1,139 @@
+//! Level hints, revealed one at a time by the player. Revealed hints can highlight grid cells and
+//! show ghosts of atoms from a solution while the player is placing atoms.
+
+use bevy::{prelude::*, render::view::RenderLayers};
+
+use crate::{LEVELS, save::SaveData};
+
+use super::{
+    atom::AtomAssets,
+    level::{CurrentLevel, Level, LevelPlacement},
+    state::GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<RevealedHints>();
+    app.add_observer(reveal_hint);
+    app.add_systems(
+        Update,
+        reset_revealed_hints.run_if(resource_changed::<CurrentLevel>),
+    );
+    app.add_systems(OnEnter(GameState::Placement), spawn_hint_ghosts);
+    app.add_systems(
+        Update,
+        (
+            spawn_hint_ghosts.run_if(resource_changed::<RevealedHints>),
+            draw_hint_cells,
+        )
+            .run_if(in_state(GameState::Placement)),
+    );
+}
+
+/// Number of the current level's hints that the player has revealed
+#[derive(Resource, Default)]
+pub struct RevealedHints(pub usize);
+
+/// Reveal the current level's next hint
+#[derive(Event)]
+pub struct RevealHint;
+
+#[derive(Component)]
+struct HintGhost;
+
+fn reset_revealed_hints(mut revealed: ResMut<RevealedHints>) {
+    revealed.0 = 0;
+}
+
+fn reveal_hint(
+    _: Trigger<RevealHint>,
+    mut revealed: ResMut<RevealedHints>,
+    mut save_data: ResMut<SaveData>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+) -> Result {
+    let level = current_level.get_level(&level_assets)?;
+    if revealed.0 >= level.hints.len() {
+        return Ok(());
+    }
+    revealed.0 += 1;
+
+    if let Some(index) = current_level.get_index() {
+        let progress = save_data.level_mut(LEVELS[index]);
+        progress.hints_used = progress.hints_used.max(revealed.0 as u32);
+        save_data.save();
+    }
+    Ok(())
+}
+
+fn spawn_hint_ghosts(
+    mut commands: Commands,
+    revealed: Res<RevealedHints>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+    atom_assets: Res<AtomAssets>,
+    ghosts: Query<Entity, With<HintGhost>>,
+) -> Result {
+    for entity in &ghosts {
+        commands.entity(entity).despawn();
+    }
+    let level = current_level.get_level(&level_assets)?;
+    for hint_atom in level
+        .hints
+        .iter()
+        .take(revealed.0)
+        .flat_map(|hint| &hint.atoms)
+    {
+        commands.spawn((
+            Name::new("Hint Ghost"),
+            HintGhost,
+            placement_ghost(hint_atom, &atom_assets),
+            StateScoped(GameState::Placement),
+        ));
+    }
+    Ok(())
+}
+
+/// A faded atom showing where a placement from a hint or solution goes
+pub fn placement_ghost(placement: &LevelPlacement, atom_assets: &AtomAssets) -> impl Bundle {
+    (
+        placement.atom_type.get_ghost_sprite(atom_assets),
+        Transform::from_xyz(
+            placement.position.x as f32,
+            placement.position.y as f32,
+            -0.1,
+        )
+        .with_scale(Vec3::splat(0.002)),
+        RenderLayers::layer(2),
+    )
+}
+
+fn draw_hint_cells(
+    mut gizmos: Gizmos,
+    revealed: Res<RevealedHints>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+) {
+    let Ok(level) = current_level.get_level(&level_assets) else {
+        return;
+    };
+    for hint in level.hints.iter().take(revealed.0) {
+        for cell in &hint.cells {
+            gizmos.rect_2d(
+                Isometry2d::from_translation(cell.as_vec2()),
+                Vec2::splat(0.8),
+                LinearRgba::rgb(1.0, 0.9, 0.3).with_alpha(0.6),
+            );
+        }
+        for hint_atom in &hint.atoms {
+            if let Some(direction) = hint_atom.direction {
+                let direction = direction.as_velocity();
+                let position = hint_atom.position.as_vec2();
+                gizmos.arrow_2d(
+                    position + (direction * 0.3),
+                    position + (direction * 1.2),
+                    LinearRgba::rgb(0.4, 0.4, 0.8).with_alpha(0.5),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Local per-level leaderboards, ranking every profile's solutions by atoms placed, time taken
+//! and reactions caused. Each entry keeps its layout so it can be replayed.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{asset_tracking::ResourceHandles, screens::Screen};
+
+use super::{
+    MenuSelection,
+    level::{PlacedAtom, PlacedLevelAtoms},
+    state::GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PendingReplay>();
+    app.add_observer(replay_layout);
+    app.add_systems(OnEnter(GameState::Placement), finish_replay);
+    app.add_systems(
+        Update,
+        start_pending_replay.run_if(in_state(GameState::Placement)),
+    );
+}
+
+/// How many entries are shown for each category
+pub const LEADERBOARD_SIZE: usize = 5;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub profile: String,
+    pub atoms_placed: u32,
+    pub ticks: u32,
+    pub reactions: u32,
+    pub layout: Vec<(IVec2, PlacedAtom)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderboardCategory {
+    FewestAtoms,
+    Fastest,
+    FewestReactions,
+}
+
+impl LeaderboardCategory {
+    pub const ALL: [Self; 3] = [Self::FewestAtoms, Self::Fastest, Self::FewestReactions];
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Self::FewestAtoms => "Fewest atoms",
+            Self::Fastest => "Fastest",
+            Self::FewestReactions => "Fewest reactions",
+        }
+    }
+
+    /// The value an entry is ranked by in this category, e.g. "3 atoms"
+    pub fn describe(self, entry: &LeaderboardEntry) -> String {
+        match self {
+            Self::FewestAtoms => format!("{} atoms", entry.atoms_placed),
+            Self::Fastest => format!("{} ticks", entry.ticks),
+            Self::FewestReactions => format!("{} reactions", entry.reactions),
+        }
+    }
+
+    /// Sort key, using the other two scores to break ties
+    fn key(self, entry: &LeaderboardEntry) -> (u32, u32, u32) {
+        match self {
+            Self::FewestAtoms => (entry.atoms_placed, entry.ticks, entry.reactions),
+            Self::Fastest => (entry.ticks, entry.atoms_placed, entry.reactions),
+            Self::FewestReactions => (entry.reactions, entry.atoms_placed, entry.ticks),
+        }
+    }
+}
+
+impl Leaderboard {
+    /// The best entries in a category, best first. Earlier entries win ties.
+    pub fn top(&self, category: LeaderboardCategory) -> Vec<&LeaderboardEntry> {
+        let mut entries: Vec<_> = self.entries.iter().collect();
+        entries.sort_by_key(|entry| category.key(entry));
+        entries.truncate(LEADERBOARD_SIZE);
+        entries
+    }
+
+    /// Add a completed solution, dropping any entries that no longer place in any category
+    pub fn record(&mut self, entry: LeaderboardEntry) {
+        // Solving a level the same way again shouldn't fill the board with copies of it
+        if self
+            .entries
+            .iter()
+            .any(|existing| existing.profile == entry.profile && existing.layout == entry.layout)
+        {
+            return;
+        }
+        self.entries.push(entry);
+
+        let mut keep = vec![false; self.entries.len()];
+        for category in LeaderboardCategory::ALL {
+            let mut ranked: Vec<_> = (0..self.entries.len()).collect();
+            ranked.sort_by_key(|&i| category.key(&self.entries[i]));
+            for &i in ranked.iter().take(LEADERBOARD_SIZE) {
+                keep[i] = true;
+            }
+        }
+        let mut keep = keep.into_iter();
+        self.entries.retain(|_| keep.next().unwrap_or(false));
+    }
+}
+
+/// Load a saved layout into a campaign level and run it
+#[derive(Event, Debug, Clone)]
+pub struct ReplayLayout {
+    pub level_index: usize,
+    pub layout: Vec<(IVec2, PlacedAtom)>,
+}
+
+/// Tracks a replay from when it's requested until its experiment is over
+#[derive(Resource, Default)]
+pub struct PendingReplay {
+    /// Set when a replay has been requested, so the experiment starts once the level is set up
+    start: bool,
+    /// Whether the current experiment is a replay. Replays don't count towards the player's
+    /// progress, scores or statistics.
+    playing: bool,
+}
+
+impl PendingReplay {
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+}
+
+/// Run condition for systems that should ignore replays
+pub fn is_replaying(pending_replay: Res<PendingReplay>) -> bool {
+    pending_replay.playing
+}
+
+fn replay_layout(
+    trigger: Trigger<ReplayLayout>,
+    screen: Res<State<Screen>>,
+    resource_handles: Res<ResourceHandles>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut menu_selection: ResMut<MenuSelection>,
+    mut placed_atoms: ResMut<PlacedLevelAtoms>,
+    mut pending_replay: ResMut<PendingReplay>,
+) {
+    let replay = trigger.event();
+    placed_atoms.set_layout(&replay.layout);
+    pending_replay.start = true;
+    pending_replay.playing = true;
+    if *screen.get() == Screen::Gameplay {
+        // Replays started during gameplay are always for the current level. Restarting it sets up
+        // the replayed layout, as `Placement` isn't entered again if the game is already there.
+        next_game_state.set(GameState::RestartLevel);
+    } else {
+        *menu_selection = MenuSelection::Level(replay.level_index);
+        if resource_handles.is_all_done() {
+            next_screen.set(Screen::Gameplay);
+        } else {
+            next_screen.set(Screen::Loading);
+        }
+    }
+}
+
+fn start_pending_replay(
+    mut pending_replay: ResMut<PendingReplay>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    if pending_replay.start {
+        pending_replay.start = false;
+        next_game_state.set(GameState::Running);
+    }
+}
+
+/// Returning to placement ends a replay, unless it's about to start
+fn finish_replay(mut pending_replay: ResMut<PendingReplay>) {
+    if !pending_replay.start {
+        pending_replay.playing = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{
+        LEVELS,
+        game::{
+            atom::AtomType,
+            chain::ChainStats,
+            hint::RevealedHints,
+            level::{CurrentLevel, Level},
+            score::{LevelScore, record_level_score},
+            solution::SolutionShown,
+            tick::ExperimentClock,
+        },
+        save::SaveData,
+    };
+
+    /// Complete the first level with a layout taken from another profile's leaderboard entry
+    fn complete_level(pending_replay: PendingReplay) -> World {
+        let layout = vec![(
+            IVec2::ZERO,
+            PlacedAtom {
+                atom_type: AtomType::Basic,
+                direction: None,
+            },
+        )];
+        let mut save_data = SaveData {
+            profiles: vec!["Someone else".to_string(), "Player".to_string()],
+            ..Default::default()
+        };
+        save_data
+            .level_mut(LEVELS[0])
+            .leaderboard
+            .record(LeaderboardEntry {
+                profile: "Someone else".to_string(),
+                atoms_placed: 1,
+                ticks: 10,
+                reactions: 1,
+                layout: layout.clone(),
+            });
+        save_data.current_profile = 1;
+
+        let mut world = World::new();
+        let mut levels = Assets::<Level>::default();
+        let level_handle = levels.add(Level::default());
+        let mut placed_atoms = PlacedLevelAtoms::default();
+        placed_atoms.set_layout(&layout);
+        world.insert_resource(levels);
+        world.insert_resource(CurrentLevel::Loaded {
+            level_handle,
+            level_index: 0,
+        });
+        world.insert_resource(placed_atoms);
+        world.insert_resource(save_data);
+        world.insert_resource(pending_replay);
+        world.init_resource::<LevelScore>();
+        world.init_resource::<ExperimentClock>();
+        world.init_resource::<ChainStats>();
+        world.init_resource::<RevealedHints>();
+        world.init_resource::<SolutionShown>();
+        world.run_system_once(record_level_score).unwrap().unwrap();
+        world
+    }
+
+    #[test]
+    fn completing_a_level_records_a_leaderboard_entry() {
+        let world = complete_level(PendingReplay::default());
+        let progress = world.resource::<SaveData>().level(LEVELS[0]).unwrap();
+        assert!(progress.completed);
+        assert_eq!(progress.leaderboard.entries.len(), 2);
+    }
+
+    #[test]
+    fn replays_dont_change_the_leaderboard() {
+        let world = complete_level(PendingReplay {
+            start: false,
+            playing: true,
+        });
+        let progress = world.resource::<SaveData>().level(LEVELS[0]).unwrap();
+        assert!(!progress.completed);
+        assert_eq!(progress.stars, 0);
+        assert_eq!(progress.leaderboard.entries.len(), 1);
+        assert_eq!(progress.leaderboard.entries[0].profile, "Someone else");
+    }
+}
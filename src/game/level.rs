@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 use bevy::{
     asset::{AssetLoader, LoadContext, io::Reader},
     input::common_conditions::input_just_released,
@@ -11,9 +13,18 @@ use thiserror::Error;
 use crate::{LEVELS, asset_tracking::LoadResource, screens::Screen};
 
 use super::{
-    atom::{AtomAssets, AtomType, atom},
+    atom::{AtomAssets, AtomType, atom, durability},
+    board::render_level,
+    chain::Lineage,
+    collision::unstable,
+    detector::detector,
+    emitter::emitter,
     movement::{CardinalDirection, Movement},
+    placement::GridPos,
+    reaction::Decay,
     state::GameState,
+    switch::{gate, set_gate_collision, switch},
+    text_level::{is_text_level, level_from_text},
     win_condition::goal,
 };
 
@@ -26,7 +37,10 @@ pub(super) fn plugin(app: &mut App) {
     app.init_resource::<PlacedLevelAtoms>();
     app.add_systems(OnEnter(Screen::Gameplay), draw_2d_grid);
     app.add_systems(OnEnter(GameState::Placement), initialise_level);
-    app.add_systems(Update, draw_arrows.run_if(in_state(GameState::Placement)));
+    app.add_systems(
+        Update,
+        (draw_arrows, draw_portal_links).run_if(in_state(GameState::Placement)),
+    );
     app.add_systems(
         Update,
         print_level_ron.run_if(input_just_released(KeyCode::F2)),
@@ -41,7 +55,9 @@ pub enum CurrentLevel {
         level_handle: Handle<Level>,
         level_index: usize,
     },
-    Editing(Level),
+    Editing(Box<Level>),
+    /// A level that isn't part of the campaign, such as a generated one
+    Custom(Handle<Level>),
 }
 
 impl CurrentLevel {
@@ -61,7 +77,8 @@ impl CurrentLevel {
             CurrentLevel::Loaded {
                 level_handle,
                 level_index: _,
-            } => level_assets
+            }
+            | CurrentLevel::Custom(level_handle) => level_assets
                 .get(level_handle)
                 .ok_or(GetLevelError::InvalidHandle),
             CurrentLevel::Editing(level) => Ok(level),
@@ -72,7 +89,7 @@ impl CurrentLevel {
         match self {
             CurrentLevel::Uninitialised => None,
             CurrentLevel::Loaded { level_index, .. } => Some(*level_index),
-            CurrentLevel::Editing(_) => None,
+            CurrentLevel::Editing(_) | CurrentLevel::Custom(_) => None,
         }
     }
 
@@ -89,16 +106,115 @@ pub enum GetLevelError {
     InvalidHandle,
 }
 
-#[derive(Asset, TypePath, Debug, Serialize, Deserialize)]
+#[derive(Asset, TypePath, Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Level {
     pub sidebar_text: String,
     pub level_complete_text: String,
     pub atoms: Vec<LevelAtom>,
     pub goal: LevelGoal,
     pub placeable_atoms: Vec<AtomType>,
+    #[serde(default)]
+    pub emitters: Vec<LevelEmitter>,
+    #[serde(default)]
+    pub detectors: Vec<LevelDetector>,
+    /// Whether the player may choose a launch direction for the atoms they place
+    #[serde(default)]
+    pub allow_moving_placement: bool,
+    #[serde(default)]
+    pub switches: Vec<LevelSwitch>,
+    #[serde(default)]
+    pub gates: Vec<LevelGate>,
+    #[serde(default)]
+    pub par: LevelPar,
+    /// Hints the player can reveal one at a time, in order
+    #[serde(default)]
+    pub hints: Vec<LevelHint>,
+    /// Known ways to complete the level, each a list of placed atoms
+    #[serde(default)]
+    pub solutions: Vec<Vec<LevelPlacement>>,
+}
+
+/// Targets for earning a full star rating. Targets that aren't set are always met.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LevelPar {
+    /// Most atoms the player may place
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atoms: Option<u32>,
+    /// Most ticks the experiment may take to complete the level
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticks: Option<u32>,
+    /// Most reactions that may happen before the level is complete
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reactions: Option<u32>,
+    /// Most hints the player may reveal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hints: Option<u32>,
+}
+
+/// A hint for a level. As well as the text, a hint can highlight grid cells or show where some of
+/// the atoms in a solution go.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelHint {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cells: Vec<IVec2>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub atoms: Vec<LevelPlacement>,
+}
+
+/// An atom placed by the player, as written in a level file for hints and solutions
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct LevelPlacement {
+    pub atom_type: AtomType,
+    pub position: IVec2,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub direction: Option<CardinalDirection>,
+}
+
+impl LevelPlacement {
+    /// Convert a list of placements into the layout stored by [`PlacedLevelAtoms`]
+    pub fn layout(placements: &[LevelPlacement]) -> Vec<(IVec2, PlacedAtom)> {
+        placements
+            .iter()
+            .map(|placement| {
+                (
+                    placement.position,
+                    PlacedAtom {
+                        atom_type: placement.atom_type,
+                        direction: placement.direction,
+                    },
+                )
+            })
+            .collect()
+    }
 }
 
 impl Level {
+    /// Portals link in pairs, so no more than two may share an id. A portal on its own is only
+    /// allowed if the player can place its partner.
+    pub fn check_portals(&self) -> Result<(), InvalidLevelError> {
+        let mut portals: HashMap<u8, usize> = HashMap::new();
+        for atom in &self.atoms {
+            if let AtomType::Portal(id) = atom.atom_type {
+                *portals.entry(id).or_default() += 1;
+            }
+        }
+        let mut portals: Vec<_> = portals.into_iter().collect();
+        portals.sort();
+        for (id, count) in portals {
+            if count > 2 {
+                return Err(InvalidLevelError::TooManyPortals { id, count });
+            } else if count == 1 && !self.placeable_atoms.contains(&AtomType::Portal(id)) {
+                return Err(InvalidLevelError::UnpairedPortal(id));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn atom_at_position_mut(&mut self, position: IVec2) -> Option<&mut LevelAtom> {
+        self.atoms.iter_mut().find(|atom| atom.position == position)
+    }
+
     pub fn remove_atom_at_position(&mut self, position: IVec2) -> Option<LevelAtom> {
         if let Some(index) = self.atoms.iter().position(|atom| atom.position == position) {
             Some(self.atoms.remove(index))
@@ -108,11 +224,26 @@ impl Level {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// A level that was read successfully, but can't be played
+#[derive(Debug, Error)]
+pub enum InvalidLevelError {
+    #[error("There are {count} portals with id {id}, but portals link in pairs")]
+    TooManyPortals { id: u8, count: usize },
+    #[error("The portal with id {0} has no partner, and one can't be placed")]
+    UnpairedPortal(u8),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LevelAtom {
     pub atom_type: AtomType,
     pub position: IVec2,
     pub velocity: Option<Movement>,
+    /// Makes the atom unstable, decaying after a number of ticks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decay: Option<Decay>,
+    /// Number of impacts a wall can take before breaking. Walls are indestructible if not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub durability: Option<u32>,
 }
 
 #[allow(dead_code)]
@@ -122,6 +253,8 @@ impl LevelAtom {
             atom_type,
             position: position.into(),
             velocity: None,
+            decay: None,
+            durability: None,
         }
     }
 
@@ -134,18 +267,70 @@ impl LevelAtom {
             atom_type,
             position: position.into(),
             velocity: Some(Movement::new(direction)),
+            decay: None,
+            durability: None,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelEmitter {
+    /// The type of atom that is emitted
+    pub atom_type: AtomType,
+    pub position: IVec2,
+    /// The direction emitted atoms travel in
+    pub direction: CardinalDirection,
+    /// Number of ticks between emissions
+    pub interval: u32,
+    /// Maximum number of atoms to emit. Unlimited if not set.
+    pub count: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelDetector {
+    /// The type of atom that is counted
+    pub atom_type: AtomType,
+    pub position: IVec2,
+    /// Only count atoms travelling in this direction. Atoms travelling in any direction are
+    /// counted if not set.
+    pub direction: Option<CardinalDirection>,
+    /// Number of atoms that must pass through to satisfy the `FillDetectors` goal
+    pub target: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelSwitch {
+    pub position: IVec2,
+    /// Only atoms of this type trigger the switch. Any atom triggers it if not set.
+    pub atom_type: Option<AtomType>,
+    /// Positions of the gates that are toggled when the switch is triggered
+    pub gates: Vec<IVec2>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LevelGate {
+    pub position: IVec2,
+    /// Whether the gate starts open
+    #[serde(default)]
+    pub open: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub enum LevelGoal {
+    #[default]
     None,
     ReachPositions(Vec<LevelGoalPosition>),
     CreateAtom(AtomType),
+    /// Every detector with a target must count at least that many atoms
+    FillDetectors,
+    /// Create an atom of the given type at the end of a chain of at least `depth` reactions
+    CreateAtomAtDepth {
+        atom_type: AtomType,
+        depth: u32,
+    },
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct LevelGoalPosition {
     pub atom_type: AtomType,
     pub position: IVec2,
@@ -161,6 +346,31 @@ impl LevelGoalPosition {
     }
 }
 
+/// Read a level file directly, without going through the asset server. Text levels are read as
+/// well as RON.
+pub fn read_level_file(path: &Path) -> Result<Level, String> {
+    let contents = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+    let level: Level = if is_text_level(path) {
+        level_from_text(&contents).map_err(|error| error.to_string())?
+    } else {
+        ron::from_str(&contents).map_err(|error| error.to_string())?
+    };
+    level.check_portals().map_err(|error| error.to_string())?;
+    Ok(level)
+}
+
+/// Paths of the level files in a folder, sorted by name
+pub fn level_files(folder: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files: Vec<_> = std::fs::read_dir(folder)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension().is_some_and(|extension| extension == "ron") || is_text_level(path)
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
 #[derive(Default)]
 struct LevelAssetLoader;
 
@@ -173,6 +383,8 @@ enum LevelAssetLoaderError {
     /// A [RON](ron) Error
     #[error("Could not parse RON: {0}")]
     RonSpannedError(#[from] ron::error::SpannedError),
+    #[error("Invalid level: {0}")]
+    Invalid(#[from] InvalidLevelError),
 }
 
 impl AssetLoader for LevelAssetLoader {
@@ -188,6 +400,7 @@ impl AssetLoader for LevelAssetLoader {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
         let custom_asset = ron::de::from_bytes::<Level>(&bytes)?;
+        custom_asset.check_portals()?;
         Ok(custom_asset)
     }
 
@@ -214,18 +427,68 @@ impl FromWorld for LevelAssets {
     }
 }
 
+/// An atom placed by the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PlacedAtom {
+    pub atom_type: AtomType,
+    /// The direction the atom is launched in, or `None` if it is stationary
+    pub direction: Option<CardinalDirection>,
+}
+
 /// Contains atoms that have been placed by the player and are not part of the level
 #[derive(Resource, Default)]
-pub struct PlacedLevelAtoms(HashMap<IVec2, AtomType>);
+pub struct PlacedLevelAtoms(HashMap<IVec2, PlacedAtom>);
 impl PlacedLevelAtoms {
     pub fn clear(&mut self) {
         self.0.clear();
     }
-    pub fn add(&mut self, atom_type: AtomType, position: IVec2) {
-        if self.0.insert(position, atom_type).is_some() {
+    pub fn add(
+        &mut self,
+        atom_type: AtomType,
+        position: IVec2,
+        direction: Option<CardinalDirection>,
+    ) {
+        if self
+            .0
+            .insert(
+                position,
+                PlacedAtom {
+                    atom_type,
+                    direction,
+                },
+            )
+            .is_some()
+        {
             warn!("Tried to place an atom in an occupied position");
         }
     }
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// The placed atoms as a list ordered by position, suitable for saving
+    pub fn layout(&self) -> Vec<(IVec2, PlacedAtom)> {
+        let mut layout: Vec<_> = self.0.iter().map(|(pos, atom)| (*pos, *atom)).collect();
+        layout.sort_by_key(|(pos, _)| (pos.x, pos.y));
+        layout
+    }
+    /// The placed atoms as they would be written in a level file's solutions
+    pub fn placements(&self) -> Vec<LevelPlacement> {
+        self.layout()
+            .into_iter()
+            .map(|(position, placed)| LevelPlacement {
+                atom_type: placed.atom_type,
+                position,
+                direction: placed.direction,
+            })
+            .collect()
+    }
+    /// Replace all placed atoms with a previously saved layout
+    pub fn set_layout(&mut self, layout: &[(IVec2, PlacedAtom)]) {
+        self.0 = layout.iter().copied().collect();
+    }
     pub fn remove(&mut self, position: &IVec2) {
         if self.0.remove(position).is_none() {
             warn!("Tried to remove a placed atom, but none existed at that location");
@@ -263,10 +526,41 @@ fn initialise_level(
         if let Some(velocity) = &level_atom.velocity {
             entity.insert(velocity.clone());
         }
+        if let Some(decay) = &level_atom.decay {
+            entity.insert(unstable(decay.clone(), &atom_assets));
+        }
+        if let Some(hits) = level_atom.durability {
+            entity.insert(durability(hits));
+        }
     }
     // Spawn placed atoms
-    for (position, atom_type) in &placed_atoms.0 {
-        commands.spawn(atom(*atom_type, *position, &atom_assets));
+    for (position, placed_atom) in &placed_atoms.0 {
+        let mut entity = commands.spawn((
+            atom(placed_atom.atom_type, *position, &atom_assets),
+            Lineage::placed(*position),
+        ));
+        if let Some(direction) = placed_atom.direction {
+            entity.insert(Movement::new(direction));
+        }
+    }
+
+    // Spawn emitters
+    for level_emitter in &level.emitters {
+        commands.spawn((emitter(level_emitter, &atom_assets), LevelEntity));
+    }
+
+    // Spawn detectors
+    for level_detector in &level.detectors {
+        commands.spawn((detector(level_detector, &atom_assets), LevelEntity));
+    }
+
+    // Spawn switches and gates
+    for level_switch in &level.switches {
+        commands.spawn((switch(level_switch, &atom_assets), LevelEntity));
+    }
+    for level_gate in &level.gates {
+        let mut entity = commands.spawn((gate(level_gate, &atom_assets), LevelEntity));
+        set_gate_collision(&mut entity, level_gate.open);
     }
 
     // Spawn goal zones
@@ -311,12 +605,32 @@ fn draw_arrows(mut gizmos: Gizmos, moving_atoms: Query<(&Movement, &Transform),
     }
 }
 
-fn print_level_ron(current_level: Res<CurrentLevel>, level_assets: Res<Assets<Level>>) -> Result {
+fn draw_portal_links(mut gizmos: Gizmos, atoms: Query<(&AtomType, &GridPos)>) {
+    let mut portals: HashMap<u8, Vec<Vec2>> = HashMap::new();
+    for (atom_type, grid_pos) in &atoms {
+        if let AtomType::Portal(id) = atom_type {
+            portals.entry(*id).or_default().push(grid_pos.0.as_vec2());
+        }
+    }
+    for (id, positions) in portals {
+        if let [start, end] = positions[..] {
+            gizmos.line_2d(start, end, AtomType::Portal(id).get_color());
+        }
+    }
+}
+
+fn print_level_ron(
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+    placed_atoms: Res<PlacedLevelAtoms>,
+) -> Result {
     let level = current_level.get_level(&level_assets)?;
     println!(
         "{}",
         ron::ser::to_string_pretty(&level, ron::ser::PrettyConfig::default()).unwrap()
     );
+    // The board is easier to read at a glance, for example when reporting a bug
+    println!("{}", render_level(level, &placed_atoms.placements()));
 
     Ok(())
 }
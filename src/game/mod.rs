@@ -4,27 +4,70 @@ use level::{CurrentLevel, Level, LevelAssets, LevelAtom, LevelGoal};
 
 use crate::{asset_tracking::LoadResource, screens::Screen};
 
+pub mod achievement;
+pub mod analysis;
 mod animation;
 mod atom;
+pub mod board;
+mod chain;
 mod collision;
+pub mod daily;
+mod detector;
+pub mod difficulty;
+mod emitter;
+pub mod generator;
+pub mod hint;
+pub mod leaderboard;
 pub mod level;
 mod movement;
 mod placement;
+mod reaction;
+pub mod replay;
+pub mod score;
+pub mod share;
+pub mod simulation;
+pub mod solution;
+pub mod solver;
 pub mod state;
+pub mod statistics;
+mod switch;
+pub mod text_level;
+mod tick;
 mod ui;
+pub mod validation;
 mod win_condition;
 
 pub(super) fn plugin(app: &mut App) {
     app.add_plugins((
         animation::plugin,
+        atom::plugin,
+        chain::plugin,
         movement::plugin,
         state::plugin,
         level::plugin,
         collision::plugin,
+        detector::plugin,
+        emitter::plugin,
+        switch::plugin,
+        tick::plugin,
         win_condition::plugin,
         placement::plugin,
+        score::plugin,
         ui::plugin,
     ));
+    app.add_plugins((
+        achievement::plugin,
+        daily::plugin,
+        difficulty::plugin,
+        hint::plugin,
+        leaderboard::plugin,
+        replay::plugin,
+        share::plugin,
+        solution::plugin,
+        solver::plugin,
+        statistics::plugin,
+        text_level::plugin,
+    ));
 
     app.register_type::<AtomAssets>();
     app.load_resource::<AtomAssets>();
@@ -37,6 +80,8 @@ pub enum MenuSelection {
     #[default]
     Editor,
     Level(usize),
+    /// A level that isn't part of the campaign, which has been added to the level assets
+    Custom(Handle<Level>),
 }
 
 fn init_level(
@@ -46,9 +91,11 @@ fn init_level(
 ) {
     if let MenuSelection::Level(index) = *menu_selection {
         current_level.set_level(level_handles.levels[index].clone(), index);
+    } else if let MenuSelection::Custom(handle) = &*menu_selection {
+        *current_level = CurrentLevel::Custom(handle.clone());
     } else {
-        *current_level = CurrentLevel::Editing(Level {
-            sidebar_text: "This is an open-ended sandbox / level editor.\nPressing F2 will export the level as text and print it to the console, which can be used to make custom levels (Although this feature is a bit half-baked, as the level files still require manual editing to add a goal)".to_string(),
+        *current_level = CurrentLevel::Editing(Box::new(Level {
+            sidebar_text: "This is an open-ended sandbox / level editor.\nScroll over a wall to change how many hits it can take before breaking, or scroll while holding a portal to change which pair it belongs to.\nPressing F2 will export the level as text and print it to the console, which can be used to make custom levels (Although this feature is a bit half-baked, as the level files still require manual editing to add a goal)".to_string(),
             level_complete_text: String::new(),
             atoms: vec![LevelAtom::new_with_velocity(
                 AtomType::Basic,
@@ -62,7 +109,10 @@ fn init_level(
                 AtomType::Reactive,
                 AtomType::Antimatter,
                 AtomType::Wall,
+                AtomType::Portal(0),
             ],
-        });
+            allow_moving_placement: true,
+            ..Default::default()
+        }));
     }
 }
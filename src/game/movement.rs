@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{AppSystems, PausableSystems};
 
-use super::state::GameState;
+use super::state::{ExperimentSystems, GameState};
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(
@@ -11,6 +11,7 @@ pub(super) fn plugin(app: &mut App) {
         move_atoms_system
             .run_if(in_state(GameState::Running))
             .in_set(AppSystems::Update)
+            .in_set(ExperimentSystems::Move)
             .in_set(PausableSystems),
     );
 }
@@ -44,7 +45,7 @@ impl Default for Movement {
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum CardinalDirection {
     N,
     E,
@@ -57,6 +58,17 @@ pub enum CardinalDirection {
 }
 
 impl CardinalDirection {
+    pub const ALL: [CardinalDirection; 8] = [
+        CardinalDirection::N,
+        CardinalDirection::NE,
+        CardinalDirection::E,
+        CardinalDirection::SE,
+        CardinalDirection::S,
+        CardinalDirection::SW,
+        CardinalDirection::W,
+        CardinalDirection::NW,
+    ];
+
     pub fn as_velocity(&self) -> Vec2 {
         match self {
             CardinalDirection::N => Vec2::new(0.0, 1.0),
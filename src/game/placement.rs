@@ -1,5 +1,6 @@
 use bevy::{
     ecs::component::{Immutable, StorageType},
+    input::mouse::MouseWheel,
     platform::collections::HashSet,
     prelude::*,
     render::view::RenderLayers,
@@ -13,8 +14,10 @@ use crate::{
 
 use super::{
     animation::Animated,
-    atom::{AtomAssets, AtomType, atom},
-    level::{CurrentLevel, LevelAtom, LevelEntity, PlacedLevelAtoms},
+    atom::{AtomAssets, AtomType, Durability, PORTAL_IDS, atom},
+    chain::Lineage,
+    level::{CurrentLevel, Level, LevelAtom, LevelEntity, PlacedLevelAtoms},
+    movement::{CardinalDirection, Movement},
     state::GameState,
 };
 
@@ -29,6 +32,8 @@ pub(super) fn plugin(app: &mut App) {
         Update,
         (
             update_drag_ghost_position_on_mouse_move,
+            rotate_ghost_launch_direction,
+            adjust_wall_durability_on_scroll,
             delete_atom_on_rightclick,
         )
             .run_if(in_state(GameState::Placement))
@@ -37,7 +42,7 @@ pub(super) fn plugin(app: &mut App) {
     );
     app.add_systems(
         Update,
-        update_dragging_state.run_if(in_state(GameState::Placement)),
+        (update_dragging_state, draw_ghost_arrow).run_if(in_state(GameState::Placement)),
     );
     app.add_observer(handle_place_atom);
 }
@@ -69,31 +74,45 @@ impl Component for GridPos {
 #[derive(Component)]
 pub struct DraggingGhost;
 
+/// The direction that the atom being dragged will be launched in once placed. `None` means the
+/// atom will be stationary.
+#[derive(Component, Default)]
+pub struct LaunchDirection(pub Option<CardinalDirection>);
+
+impl LaunchDirection {
+    /// Cycle through stationary and each of the eight directions
+    fn rotate(&mut self, clockwise: bool) {
+        self.0 = match (self.0, clockwise) {
+            (None, true) => Some(CardinalDirection::N),
+            (None, false) => Some(CardinalDirection::NW),
+            (Some(CardinalDirection::NW), true) | (Some(CardinalDirection::N), false) => None,
+            (Some(direction), true) => Some(direction.clockwise()),
+            (Some(direction), false) => Some(direction.anticlockwise()),
+        };
+    }
+}
+
 fn update_drag_ghost_position_on_mouse_move(
     mut query: Query<&mut Transform, With<DraggingGhost>>,
     window: Single<&Window, With<PrimaryWindow>>,
     camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
     occupied_positions: Res<OccupiedGridPositions>,
 ) {
-    if let Some(mouse_pos) = window.cursor_position() {
-        if let Ok(mut transform) = query.single_mut() {
-            if let Some((camera, camera_transform)) =
-                camera_query.iter().find(|(cam, _)| cam.order == 2)
-            {
-                // Convert screen coordinates to world coordinates
-                if let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, mouse_pos) {
-                    let grid_pos =
-                        IVec2::new(world_pos.x.round() as i32, world_pos.y.round() as i32);
-
-                    if occupied_positions.contains(&grid_pos) {
-                        return;
-                    }
+    if let Some(mouse_pos) = window.cursor_position()
+        && let Ok(mut transform) = query.single_mut()
+        && let Some((camera, camera_transform)) =
+            camera_query.iter().find(|(cam, _)| cam.order == 2)
+        // Convert screen coordinates to world coordinates
+        && let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, mouse_pos)
+    {
+        let grid_pos = IVec2::new(world_pos.x.round() as i32, world_pos.y.round() as i32);
 
-                    transform.translation.x = grid_pos.x as f32;
-                    transform.translation.y = grid_pos.y as f32;
-                }
-            }
+        if occupied_positions.contains(&grid_pos) {
+            return;
         }
+
+        transform.translation.x = grid_pos.x as f32;
+        transform.translation.y = grid_pos.y as f32;
     }
 }
 
@@ -108,6 +127,7 @@ pub fn atom_placement_ghost(
         atom_type.get_sprite(atom_assets),
         atom_type,
         DraggingGhost,
+        LaunchDirection::default(),
         Animated::new(8),
         Transform::from_scale(Vec3::splat(0.002)),
         RenderLayers::layer(2),
@@ -118,35 +138,179 @@ pub fn atom_placement_ghost(
 #[derive(Event)]
 pub struct PlaceGhostAtom;
 
+/// Triggered after the player places an atom on the grid
+#[derive(Event)]
+pub struct AtomPlaced(pub AtomType);
+
+/// Portals can't be launched, so while editing a level, scrolling with a portal changes which pair
+/// it belongs to instead
+fn rotate_ghost_launch_direction(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut ghost_query: Query<(&mut LaunchDirection, &mut AtomType, &mut Sprite), With<DraggingGhost>>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+) {
+    let scroll: f32 = mouse_wheel.read().map(|event| event.y).sum();
+    let Ok((mut launch_direction, mut atom_type, mut sprite)) = ghost_query.single_mut() else {
+        return;
+    };
+    let clockwise =
+        if scroll > 0.0 || keys.any_just_pressed([KeyCode::ArrowRight, KeyCode::ArrowUp]) {
+            true
+        } else if scroll < 0.0 || keys.any_just_pressed([KeyCode::ArrowLeft, KeyCode::ArrowDown]) {
+            false
+        } else {
+            return;
+        };
+
+    if let AtomType::Portal(id) = *atom_type {
+        if current_level.is_editing() {
+            let id = if clockwise {
+                (id + 1) % PORTAL_IDS
+            } else {
+                (id + PORTAL_IDS - 1) % PORTAL_IDS
+            };
+            *atom_type = AtomType::Portal(id);
+            sprite.color = atom_type.get_color();
+        }
+        return;
+    }
+    if !current_level
+        .get_level(&level_assets)
+        .is_ok_and(|level| level.allow_moving_placement)
+    {
+        return;
+    }
+
+    launch_direction.rotate(clockwise);
+}
+
+/// While editing a level, scrolling over a wall changes how many impacts it can take. Scrolling
+/// down to zero makes the wall indestructible.
+fn adjust_wall_durability_on_scroll(
+    mut mouse_wheel: EventReader<MouseWheel>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+    ghost_query: Query<(), With<DraggingGhost>>,
+    walls_query: Query<(&AtomType, &GridPos, Option<&Durability>)>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let scroll: f32 = mouse_wheel.read().map(|event| event.y).sum();
+    if scroll == 0.0 || !ghost_query.is_empty() || !current_level.is_editing() {
+        return;
+    }
+    let Some(grid_pos) = cursor_grid_position(&window, &camera_query) else {
+        return;
+    };
+    let Some(durability) = walls_query
+        .iter()
+        .find(|(atom_type, wall_pos, _)| **atom_type == AtomType::Wall && wall_pos.0 == grid_pos)
+        .map(|(_, _, durability)| durability.map_or(0, |durability| durability.max))
+    else {
+        return;
+    };
+
+    let durability = if scroll > 0.0 {
+        durability + 1
+    } else {
+        durability.saturating_sub(1)
+    };
+    if let CurrentLevel::Editing(level) = &mut *current_level
+        && let Some(level_atom) = level.atom_at_position_mut(grid_pos)
+    {
+        level_atom.durability = (durability > 0).then_some(durability);
+        // Respawn the level so that the wall picks up its new durability
+        next_state.set(GameState::RestartLevel);
+    }
+}
+
+/// Get the grid position under the mouse cursor
+fn cursor_grid_position(
+    window: &Window,
+    camera_query: &Query<(&Camera, &GlobalTransform), With<Camera2d>>,
+) -> Option<IVec2> {
+    let mouse_pos = window.cursor_position()?;
+    let (camera, camera_transform) = camera_query.iter().find(|(cam, _)| cam.order == 2)?;
+    // Convert screen coordinates to world coordinates
+    let world_pos = camera
+        .viewport_to_world_2d(camera_transform, mouse_pos)
+        .ok()?;
+    Some(IVec2::new(
+        world_pos.x.round() as i32,
+        world_pos.y.round() as i32,
+    ))
+}
+
+fn draw_ghost_arrow(
+    mut gizmos: Gizmos,
+    ghost_query: Query<(&LaunchDirection, &Transform), With<DraggingGhost>>,
+) {
+    for (launch_direction, transform) in &ghost_query {
+        if let Some(direction) = launch_direction.0 {
+            let direction = direction.as_velocity();
+            let position = transform.translation.xy();
+            gizmos.arrow_2d(
+                position + (direction * 0.3),
+                position + (direction * 1.2),
+                LinearRgba::rgb(0.4, 0.4, 0.8).with_alpha(0.5),
+            );
+        }
+    }
+}
+
 fn handle_place_atom(
     _trigger: Trigger<PlaceGhostAtom>,
     mut commands: Commands,
-    ghost_query: Query<(Entity, &AtomType, &Transform), With<DraggingGhost>>,
+    ghost_query: Query<(Entity, &AtomType, &LaunchDirection, &Transform), With<DraggingGhost>>,
+    board_atoms: Query<&AtomType, (With<GridPos>, Without<DraggingGhost>)>,
     atom_assets: Res<AtomAssets>,
     mut current_level: ResMut<CurrentLevel>,
     mut placed_atoms: ResMut<PlacedLevelAtoms>,
     audio_assets: Res<InteractionAssets>,
 ) {
-    if let Ok((entity, atom_type, transform)) = ghost_query.single() {
+    if let Ok((entity, atom_type, launch_direction, transform)) = ghost_query.single() {
         // Despawn the ghost
         commands.entity(entity).despawn();
+        // Portals link in pairs, so a third portal would have nowhere to send atoms
+        if let AtomType::Portal(id) = atom_type
+            && board_atoms
+                .iter()
+                .filter(|board_atom| *board_atom == atom_type)
+                .count()
+                >= 2
+        {
+            warn!("There are already two portals with id {id}");
+            return;
+        }
         // Spawn the actual atom
         let grid_pos = IVec2::new(
             transform.translation.x.round() as i32,
             transform.translation.y.round() as i32,
         );
         let mut entity = commands.spawn(atom(*atom_type, grid_pos, &atom_assets));
+        if let Some(direction) = launch_direction.0 {
+            entity.insert(Movement::new(direction));
+        }
 
         if current_level.is_editing() {
             if let CurrentLevel::Editing(level) = &mut *current_level {
                 // If we're editing a level, add the atom to the level data
-                level.atoms.push(LevelAtom::new(*atom_type, grid_pos));
+                level.atoms.push(match launch_direction.0 {
+                    Some(direction) => {
+                        LevelAtom::new_with_velocity(*atom_type, grid_pos, direction)
+                    }
+                    None => LevelAtom::new(*atom_type, grid_pos),
+                });
                 entity.insert(LevelEntity);
             }
         } else {
-            placed_atoms.add(*atom_type, grid_pos);
+            entity.insert(Lineage::placed(grid_pos));
+            placed_atoms.add(*atom_type, grid_pos, launch_direction.0);
         }
 
+        commands.trigger(AtomPlaced(*atom_type));
         commands.spawn(sound_effect(audio_assets.click.clone()));
     }
 }
@@ -200,43 +364,37 @@ fn delete_atom_on_rightclick(
     mut commands: Commands,
     audio_assets: Res<InteractionAssets>,
 ) {
-    if buttons.just_released(MouseButton::Right) {
-        if let Some(mouse_pos) = window.cursor_position() {
-            if let Some((camera, camera_transform)) =
-                camera_query.iter().find(|(cam, _)| cam.order == 2)
-            {
-                // Convert screen coordinates to world coordinates
-                if let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, mouse_pos) {
-                    let nearest_grid_pos =
-                        IVec2::new(world_pos.x.round() as i32, world_pos.y.round() as i32);
-                    for (entity, transform, maybe_level_entity) in atoms_query {
-                        let nearest_atom_grid_pos = IVec2::new(
-                            transform.translation.x.round() as i32,
-                            transform.translation.y.round() as i32,
+    if buttons.just_released(MouseButton::Right)
+        && let Some(mouse_pos) = window.cursor_position()
+        && let Some((camera, camera_transform)) =
+            camera_query.iter().find(|(cam, _)| cam.order == 2)
+        // Convert screen coordinates to world coordinates
+        && let Ok(world_pos) = camera.viewport_to_world_2d(camera_transform, mouse_pos)
+    {
+        let nearest_grid_pos = IVec2::new(world_pos.x.round() as i32, world_pos.y.round() as i32);
+        for (entity, transform, maybe_level_entity) in atoms_query {
+            let nearest_atom_grid_pos = IVec2::new(
+                transform.translation.x.round() as i32,
+                transform.translation.y.round() as i32,
+            );
+            if current_level.is_editing() {
+                if let CurrentLevel::Editing(level) = &mut *current_level
+                    && nearest_grid_pos == nearest_atom_grid_pos
+                {
+                    commands.entity(entity).despawn();
+                    commands.spawn(sound_effect(audio_assets.click.clone()));
+                    if level.remove_atom_at_position(nearest_grid_pos).is_none() {
+                        warn!(
+                            "Deleted atom while editing a level, but the atom didn't exist in the level!"
                         );
-                        if current_level.is_editing() {
-                            if let CurrentLevel::Editing(level) = &mut *current_level {
-                                if nearest_grid_pos == nearest_atom_grid_pos {
-                                    commands.entity(entity).despawn();
-                                    commands.spawn(sound_effect(audio_assets.click.clone()));
-                                    if level.remove_atom_at_position(nearest_grid_pos).is_none() {
-                                        warn!(
-                                            "Deleted atom while editing a level, but the atom didn't exist in the level!"
-                                        );
-                                    }
-                                    return;
-                                }
-                            }
-                        } else if maybe_level_entity.is_none()
-                            && nearest_grid_pos == nearest_atom_grid_pos
-                        {
-                            commands.entity(entity).despawn();
-                            commands.spawn(sound_effect(audio_assets.click.clone()));
-                            placed_atoms.remove(&nearest_grid_pos);
-                            return;
-                        }
                     }
+                    return;
                 }
+            } else if maybe_level_entity.is_none() && nearest_grid_pos == nearest_atom_grid_pos {
+                commands.entity(entity).despawn();
+                commands.spawn(sound_effect(audio_assets.click.clone()));
+                placed_atoms.remove(&nearest_grid_pos);
+                return;
             }
         }
     }
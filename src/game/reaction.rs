@@ -0,0 +1,275 @@
+//! The rules for what happens when atoms collide or decay.
+//!
+//! These are kept free of any ECS state so that the outcome of a reaction can be worked out (and
+//! reported) separately from spawning the resulting atoms.
+
+use serde::{Deserialize, Serialize};
+
+use super::{atom::AtomType, movement::CardinalDirection};
+
+/// An atom taking part in a reaction, along with its direction of travel (if it is moving)
+pub type Reactant = (AtomType, Option<CardinalDirection>);
+
+/// The result of a reaction
+#[derive(Debug, Clone)]
+pub struct ReactionOutcome {
+    /// Which of the reacting atoms are destroyed, in the same order as the reactants
+    pub destroyed: Vec<bool>,
+    /// Atoms created by the reaction, with their direction of travel
+    pub products: Vec<Reactant>,
+    /// Index of the reactant whose speed the products keep. Otherwise they move at the default
+    /// speed.
+    pub keep_speed_of: Option<usize>,
+    /// Whether the products ignore collisions for a short while after being created
+    pub cooldown: bool,
+    pub sound: ReactionSound,
+    /// Whether an atom just bounced off a wall, which isn't counted as a reaction
+    pub bounce: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReactionSound {
+    Merge,
+    Split,
+    SplitBig,
+    Hit,
+}
+
+impl ReactionOutcome {
+    fn new(destroyed: Vec<bool>, products: Vec<Reactant>, sound: ReactionSound) -> Self {
+        Self {
+            destroyed,
+            products,
+            keep_speed_of: None,
+            cooldown: true,
+            sound,
+            bounce: false,
+        }
+    }
+
+    fn keeping_speed_of(mut self, index: Option<usize>) -> Self {
+        self.keep_speed_of = index;
+        self
+    }
+
+    fn bouncing(mut self) -> Self {
+        self.bounce = true;
+        self
+    }
+
+    fn without_cooldown(mut self) -> Self {
+        self.cooldown = false;
+        self
+    }
+
+    /// Both atoms are destroyed and replaced by the products
+    fn replace(products: Vec<Reactant>, sound: ReactionSound) -> Self {
+        Self::new(vec![true, true], products, sound)
+    }
+}
+
+/// Work out what happens when atoms collide. Returns `None` if the atoms don't react.
+///
+/// Portals are not handled here, as atoms pass through them rather than reacting with them.
+pub fn react(reactants: &[Reactant]) -> Option<ReactionOutcome> {
+    if reactants.len() < 2 {
+        return None;
+    } else if reactants.len() >= 3 {
+        // 3 way collision always spawns a (motionless) reactive atom
+        return Some(ReactionOutcome::new(
+            vec![true; reactants.len()],
+            vec![(AtomType::Reactive, None)],
+            ReactionSound::SplitBig,
+        ));
+    }
+
+    let [(atom_type1, direction1), (atom_type2, direction2)] = [reactants[0], reactants[1]];
+    let direction = direction1.or(direction2);
+    // Index of the atom that `direction` was taken from
+    let moving = if direction1.is_some() {
+        Some(0)
+    } else if direction2.is_some() {
+        Some(1)
+    } else {
+        None
+    };
+
+    // Handle collision based on atom types
+    let outcome = match (atom_type1, atom_type2) {
+        // Wall and antimatter - destroy both
+        (AtomType::Wall, AtomType::Antimatter) | (AtomType::Antimatter, AtomType::Wall) => {
+            ReactionOutcome::replace(vec![], ReactionSound::Merge)
+        }
+
+        // Wall collisions - bounce the non-wall atom, which keeps its speed
+        (AtomType::Wall, other_type) => ReactionOutcome::new(
+            vec![false, true],
+            bounce(other_type, direction2),
+            ReactionSound::Hit,
+        )
+        .keeping_speed_of(Some(1))
+        .bouncing(),
+        (other_type, AtomType::Wall) => ReactionOutcome::new(
+            vec![true, false],
+            bounce(other_type, direction1),
+            ReactionSound::Hit,
+        )
+        .keeping_speed_of(Some(0))
+        .bouncing(),
+
+        // Two basic atoms fuse into a splitting atom, carrying on at the same speed
+        (AtomType::Basic, AtomType::Basic) => {
+            ReactionOutcome::replace(vec![(AtomType::Splitting, direction)], ReactionSound::Merge)
+                .keeping_speed_of(moving)
+        }
+
+        // two splitting atoms split into 3
+        (AtomType::Splitting, AtomType::Splitting) => ReactionOutcome::replace(
+            direction
+                .map(|direction| {
+                    vec![
+                        (AtomType::Basic, Some(direction)),
+                        (AtomType::Basic, Some(direction.clockwise().clockwise())),
+                        (
+                            AtomType::Basic,
+                            Some(direction.anticlockwise().anticlockwise()),
+                        ),
+                    ]
+                })
+                .unwrap_or_default(),
+            ReactionSound::Split,
+        ),
+
+        // Reactives combine into antimatter
+        (AtomType::Reactive, AtomType::Reactive) => ReactionOutcome::replace(
+            direction
+                .map(|direction| vec![(AtomType::Antimatter, Some(direction))])
+                .unwrap_or_default(),
+            ReactionSound::Merge,
+        ),
+
+        // Basic pushes reactive
+        (AtomType::Basic, AtomType::Reactive) | (AtomType::Reactive, AtomType::Basic) => {
+            ReactionOutcome::replace(
+                direction
+                    .map(|direction| vec![(AtomType::Reactive, Some(direction))])
+                    .unwrap_or_default(),
+                ReactionSound::Hit,
+            )
+        }
+
+        // Reactive and splitting split into 4
+        (AtomType::Reactive, AtomType::Splitting) | (AtomType::Splitting, AtomType::Reactive) => {
+            ReactionOutcome::replace(
+                direction
+                    .map(|direction| {
+                        vec![
+                            (AtomType::Basic, Some(direction.clockwise())),
+                            (
+                                AtomType::Basic,
+                                Some(direction.clockwise().clockwise().clockwise()),
+                            ),
+                            (AtomType::Basic, Some(direction.anticlockwise())),
+                            (
+                                AtomType::Basic,
+                                Some(direction.anticlockwise().anticlockwise().anticlockwise()),
+                            ),
+                        ]
+                    })
+                    .unwrap_or_default(),
+                ReactionSound::SplitBig,
+            )
+        }
+
+        // Basic and antimatter - similar to splitting but in opposite direction
+        (AtomType::Basic, AtomType::Antimatter) | (AtomType::Antimatter, AtomType::Basic) => {
+            ReactionOutcome::replace(
+                direction
+                    .map(|direction| {
+                        // Spawn basic atoms at 45 degree angles
+                        let (dir1, dir2) = get_split_directions(direction.opposite());
+                        vec![(AtomType::Basic, Some(dir1)), (AtomType::Basic, Some(dir2))]
+                    })
+                    .unwrap_or_default(),
+                ReactionSound::Split,
+            )
+        }
+
+        // Splitting atom collisions - split
+        (AtomType::Splitting, AtomType::Basic) | (AtomType::Basic, AtomType::Splitting) => {
+            match direction {
+                Some(direction) => {
+                    // Spawn basic atoms at 45 degree angles
+                    let (dir1, dir2) = get_split_directions(direction);
+                    ReactionOutcome::replace(
+                        vec![(AtomType::Basic, Some(dir1)), (AtomType::Basic, Some(dir2))],
+                        ReactionSound::Split,
+                    )
+                }
+                None => {
+                    ReactionOutcome::replace(vec![(AtomType::Basic, None)], ReactionSound::Split)
+                        .without_cooldown()
+                }
+            }
+        }
+
+        // Antimatter phases through other atoms
+        (AtomType::Antimatter, _) | (_, AtomType::Antimatter) => return None,
+
+        // Portals move atoms rather than reacting with them
+        (AtomType::Portal(_), _) | (_, AtomType::Portal(_)) => return None,
+    };
+    Some(outcome)
+}
+
+/// Moving atoms bounce off walls by reversing their direction. Stationary atoms are destroyed.
+fn bounce(atom_type: AtomType, direction: Option<CardinalDirection>) -> Vec<Reactant> {
+    direction
+        .map(|direction| vec![(atom_type, Some(direction.opposite()))])
+        .unwrap_or_default()
+}
+
+/// Describes how an unstable atom decays
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Decay {
+    /// Number of ticks after the experiment starts before the atom decays
+    pub ticks: u32,
+    pub product: DecayProduct,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub enum DecayProduct {
+    /// The atom turns into a different type, keeping its velocity
+    Transform(AtomType),
+    /// The atom splits into two atoms of the given type. Moving atoms split at 45 degree angles
+    /// to their direction of travel, and stationary atoms release them to the east and west.
+    Split(AtomType),
+}
+
+impl DecayProduct {
+    /// Work out the atoms that an unstable atom decays into
+    pub fn products(&self, direction: Option<CardinalDirection>) -> Vec<Reactant> {
+        match *self {
+            DecayProduct::Transform(atom_type) => vec![(atom_type, direction)],
+            DecayProduct::Split(atom_type) => {
+                let (dir1, dir2) = match direction {
+                    Some(direction) => get_split_directions(direction),
+                    None => (CardinalDirection::E, CardinalDirection::W),
+                };
+                vec![(atom_type, Some(dir1)), (atom_type, Some(dir2))]
+            }
+        }
+    }
+
+    pub fn sound(&self) -> ReactionSound {
+        match self {
+            DecayProduct::Transform(_) => ReactionSound::Merge,
+            DecayProduct::Split(_) => ReactionSound::Split,
+        }
+    }
+}
+
+/// Get the two directions for splitting at 45 degree angles
+fn get_split_directions(direction: CardinalDirection) -> (CardinalDirection, CardinalDirection) {
+    (direction.clockwise(), direction.anticlockwise())
+}
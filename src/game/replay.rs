@@ -0,0 +1,412 @@
+//! Recording experiments on campaign levels as replay files, and playing them back.
+//!
+//! A replay stores the atoms the player placed and anything they did while the experiment ran.
+//! It also records which level it was made on, a hash of that level's contents and the version of
+//! the rules, so that a replay is refused rather than played back wrongly when the level or the
+//! rules change.
+//!
+//! Replays are written to the `replays` folder. Web builds have nowhere to write files, so replays
+//! only last for the current session there.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use web_time::{SystemTime, UNIX_EPOCH};
+
+use crate::{LEVELS, save::SaveData, screens::Screen};
+
+use super::{
+    leaderboard::ReplayLayout,
+    level::{CurrentLevel, Level, LevelAssets, LevelPlacement, PlacedLevelAtoms},
+    simulation::SIMULATION_VERSION,
+    state::GameState,
+    tick::ExperimentClock,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(ReplayLibrary::load());
+    app.init_resource::<LastExperiment>();
+    app.add_observer(save_replay);
+    app.add_observer(play_replay);
+    app.add_systems(OnEnter(GameState::Running), start_recording);
+    app.add_systems(
+        OnTransition {
+            exited: GameState::Running,
+            entered: GameState::Placement,
+        },
+        record_stop,
+    );
+    app.add_systems(
+        OnTransition {
+            exited: GameState::Running,
+            entered: GameState::RestartLevel,
+        },
+        record_stop,
+    );
+    app.add_systems(OnEnter(GameState::LevelComplete), record_completion);
+    app.add_systems(
+        Update,
+        apply_replay_inputs
+            .run_if(in_state(GameState::Running).and(resource_exists::<ReplayInputs>)),
+    );
+    app.add_systems(OnExit(GameState::Running), finish_playback);
+    app.add_systems(OnExit(Screen::Gameplay), finish_playback);
+}
+
+#[cfg(not(target_family = "wasm"))]
+const REPLAY_FOLDER: &str = "replays";
+
+/// A recorded experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    /// The [`SIMULATION_VERSION`] the replay was recorded with
+    pub simulation_version: u32,
+    /// File name of the campaign level, e.g. "3.ron"
+    pub level: String,
+    /// Hash of the level's contents when the replay was recorded
+    pub level_hash: u64,
+    pub profile: String,
+    /// When the replay was recorded, in milliseconds since the Unix epoch
+    pub recorded_at: u64,
+    pub placements: Vec<LevelPlacement>,
+    /// Things the player did while the experiment was running
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<ReplayInput>,
+    /// Ticks the experiment ran for
+    pub ticks: u32,
+    pub completed: bool,
+}
+
+/// Something the player did while an experiment was running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReplayInput {
+    /// The experiment was stopped on the given tick
+    Stop { tick: u32 },
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("Recorded with version {0} of the rules, but this is version {SIMULATION_VERSION}")]
+    RulesChanged(u32),
+    #[error("{0} isn't a campaign level")]
+    UnknownLevel(String),
+    #[error("{0} has changed since this was recorded")]
+    LevelChanged(String),
+    #[error("Levels haven't finished loading")]
+    NotLoaded,
+}
+
+impl Replay {
+    /// Index of the replay's campaign level
+    pub fn level_index(&self) -> Result<usize, ReplayError> {
+        LEVELS
+            .iter()
+            .position(|file| *file == self.level)
+            .ok_or_else(|| ReplayError::UnknownLevel(self.level.clone()))
+    }
+
+    /// Check that the replay will play back the same way it was recorded
+    pub fn check(&self, level: &Level) -> Result<(), ReplayError> {
+        if self.simulation_version != SIMULATION_VERSION {
+            return Err(ReplayError::RulesChanged(self.simulation_version));
+        }
+        if self.level_hash != level_hash(level) {
+            return Err(ReplayError::LevelChanged(self.level.clone()));
+        }
+        Ok(())
+    }
+
+    /// e.g. "Level 4 - Player - completed in 12 ticks"
+    pub fn describe(&self) -> String {
+        let level = match self.level_index() {
+            Ok(index) => format!("Level {}", index + 1),
+            Err(_) => self.level.clone(),
+        };
+        let outcome = if self.completed {
+            "completed in"
+        } else {
+            "stopped after"
+        };
+        format!(
+            "{level} - {} - {outcome} {} ticks",
+            self.profile, self.ticks
+        )
+    }
+}
+
+/// A hash of everything in a level that affects how an experiment plays out. Text and hints are
+/// left out, so that rewording a level doesn't invalidate its replays.
+pub fn level_hash(level: &Level) -> u64 {
+    let contents = ron::to_string(&(
+        &level.atoms,
+        &level.goal,
+        &level.placeable_atoms,
+        &level.emitters,
+        &level.detectors,
+        level.allow_moving_placement,
+        &level.switches,
+        &level.gates,
+    ))
+    .unwrap_or_default();
+    // FNV-1a, which unlike the standard library's hasher is the same on every platform and version
+    contents.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Every replay that has been saved, oldest first
+#[derive(Resource, Default)]
+pub struct ReplayLibrary(pub Vec<Replay>);
+
+impl ReplayLibrary {
+    #[cfg(not(target_family = "wasm"))]
+    fn load() -> Self {
+        let Ok(entries) = std::fs::read_dir(REPLAY_FOLDER) else {
+            return Self::default();
+        };
+        let mut replays: Vec<Replay> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+            .filter_map(|path| {
+                let contents = std::fs::read_to_string(&path).ok()?;
+                ron::from_str(&contents)
+                    .inspect_err(|err| warn!("Couldn't read replay {}: {err}", path.display()))
+                    .ok()
+            })
+            .collect();
+        replays.sort_by_key(|replay| replay.recorded_at);
+        Self(replays)
+    }
+
+    #[cfg(target_family = "wasm")]
+    fn load() -> Self {
+        Self::default()
+    }
+
+    /// Add a replay to the library, and write it to disk
+    pub fn add(&mut self, replay: Replay) {
+        #[cfg(not(target_family = "wasm"))]
+        {
+            let stem = replay.level.trim_end_matches(".ron");
+            let path = std::path::Path::new(REPLAY_FOLDER)
+                .join(format!("{stem}-{}.ron", replay.recorded_at));
+            let result = std::fs::create_dir_all(REPLAY_FOLDER)
+                .map_err(|err| err.to_string())
+                .and_then(|()| ron::to_string(&replay).map_err(|err| err.to_string()))
+                .and_then(|contents| {
+                    std::fs::write(&path, contents).map_err(|err| err.to_string())
+                });
+            if let Err(err) = result {
+                error!("Failed to write replay: {err}");
+            }
+        }
+        self.0.push(replay);
+    }
+}
+
+/// The most recent experiment on a campaign level, ready to be saved as a replay
+#[derive(Resource, Default)]
+struct LastExperiment(Option<Replay>);
+
+/// Save the most recent experiment as a replay
+#[derive(Event)]
+pub struct SaveReplay;
+
+/// Play back a replay, unless it no longer matches its level or the rules
+#[derive(Event)]
+pub struct PlayReplay(pub Replay);
+
+/// Inputs still to be applied to the replay being played back
+#[derive(Resource)]
+struct ReplayInputs(Vec<ReplayInput>);
+
+fn start_recording(
+    mut last_experiment: ResMut<LastExperiment>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+    placed_atoms: Res<PlacedLevelAtoms>,
+    save_data: Res<SaveData>,
+) {
+    last_experiment.0 = None;
+    let (Some(index), Ok(level)) = (
+        current_level.get_index(),
+        current_level.get_level(&level_assets),
+    ) else {
+        return;
+    };
+    let recorded_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    last_experiment.0 = Some(Replay {
+        simulation_version: SIMULATION_VERSION,
+        level: LEVELS[index].to_string(),
+        level_hash: level_hash(level),
+        profile: save_data.profile_name().to_string(),
+        recorded_at,
+        placements: placed_atoms.placements(),
+        inputs: Vec::new(),
+        ticks: 0,
+        completed: false,
+    });
+}
+
+fn record_stop(mut last_experiment: ResMut<LastExperiment>, clock: Res<ExperimentClock>) {
+    if let Some(replay) = &mut last_experiment.0 {
+        replay.inputs.push(ReplayInput::Stop { tick: clock.tick() });
+        replay.ticks = clock.tick();
+    }
+}
+
+fn record_completion(mut last_experiment: ResMut<LastExperiment>, clock: Res<ExperimentClock>) {
+    if let Some(replay) = &mut last_experiment.0 {
+        replay.completed = true;
+        replay.ticks = clock.tick();
+    }
+}
+
+fn save_replay(
+    _: Trigger<SaveReplay>,
+    last_experiment: Res<LastExperiment>,
+    mut library: ResMut<ReplayLibrary>,
+) {
+    // Replays are only saved once the experiment has finished
+    match &last_experiment.0 {
+        Some(replay) if replay.completed || !replay.inputs.is_empty() => {
+            if !library
+                .0
+                .iter()
+                .any(|saved| saved.recorded_at == replay.recorded_at && saved.level == replay.level)
+            {
+                library.add(replay.clone());
+                info!("Saved replay: {}", replay.describe());
+            }
+        }
+        _ => warn!("There's no finished experiment on a campaign level to save"),
+    }
+}
+
+fn play_replay(
+    trigger: Trigger<PlayReplay>,
+    mut commands: Commands,
+    level_handles: Option<Res<LevelAssets>>,
+    level_assets: Res<Assets<Level>>,
+) {
+    let replay = &trigger.event().0;
+    if let Err(err) = check_replay(replay, level_handles.as_deref(), &level_assets) {
+        warn!("Refusing to play replay: {err}");
+        return;
+    }
+    let Ok(level_index) = replay.level_index() else {
+        return;
+    };
+    commands.insert_resource(ReplayInputs(replay.inputs.clone()));
+    commands.trigger(ReplayLayout {
+        level_index,
+        layout: LevelPlacement::layout(&replay.placements),
+    });
+}
+
+/// Check a replay against the campaign level it was recorded on
+pub fn check_replay(
+    replay: &Replay,
+    level_handles: Option<&LevelAssets>,
+    level_assets: &Assets<Level>,
+) -> Result<(), ReplayError> {
+    let level_index = replay.level_index()?;
+    let level = level_handles
+        .and_then(|handles| level_assets.get(&handles.levels[level_index]))
+        .ok_or(ReplayError::NotLoaded)?;
+    replay.check(level)
+}
+
+fn apply_replay_inputs(
+    mut inputs: ResMut<ReplayInputs>,
+    clock: Res<ExperimentClock>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(&ReplayInput::Stop { tick }) = inputs.0.first() else {
+        return;
+    };
+    if clock.tick() >= tick {
+        inputs.0.remove(0);
+        next_state.set(GameState::Placement);
+    }
+}
+
+fn finish_playback(mut commands: Commands) {
+    commands.remove_resource::<ReplayInputs>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{
+        atom::AtomType,
+        level::{LevelHint, read_level_file},
+    };
+
+    fn campaign_level() -> Level {
+        let path = format!("{}/assets/levels/{}", env!("CARGO_MANIFEST_DIR"), LEVELS[0]);
+        read_level_file(path.as_ref()).unwrap()
+    }
+
+    fn replay_of(level: &Level) -> Replay {
+        Replay {
+            simulation_version: SIMULATION_VERSION,
+            level: LEVELS[0].to_string(),
+            level_hash: level_hash(level),
+            profile: "Player".to_string(),
+            recorded_at: 0,
+            placements: level.solutions.first().cloned().unwrap_or_default(),
+            inputs: Vec::new(),
+            ticks: 3,
+            completed: true,
+        }
+    }
+
+    #[test]
+    fn replays_with_other_rules_are_rejected() {
+        let level = campaign_level();
+        let mut replay = replay_of(&level);
+        assert!(replay.check(&level).is_ok());
+        replay.simulation_version = SIMULATION_VERSION - 1;
+        assert!(matches!(
+            replay.check(&level),
+            Err(ReplayError::RulesChanged(version)) if version == SIMULATION_VERSION - 1
+        ));
+    }
+
+    #[test]
+    fn replays_of_changed_levels_are_rejected() {
+        let level = campaign_level();
+        let replay = replay_of(&level);
+        let mut changed = level.clone();
+        changed.placeable_atoms.push(AtomType::Wall);
+        assert!(matches!(
+            replay.check(&changed),
+            Err(ReplayError::LevelChanged(file)) if file == LEVELS[0]
+        ));
+        let mut moved = level.clone();
+        moved.atoms[0].position.x += 1;
+        assert!(matches!(
+            replay.check(&moved),
+            Err(ReplayError::LevelChanged(_))
+        ));
+    }
+
+    #[test]
+    fn level_hash_ignores_text_and_hints() {
+        let level = campaign_level();
+        let mut reworded = level.clone();
+        reworded.sidebar_text = "Something else entirely".to_string();
+        reworded.level_complete_text = "Well done!".to_string();
+        reworded.hints.push(LevelHint {
+            text: "Try placing an atom".to_string(),
+            cells: vec![IVec2::ZERO],
+            atoms: Vec::new(),
+        });
+        assert_eq!(level_hash(&reworded), level_hash(&level));
+        assert!(replay_of(&level).check(&reworded).is_ok());
+    }
+}
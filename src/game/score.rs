@@ -0,0 +1,107 @@
+//! Star ratings for completed levels, based on how the player's solution compares to the level's
+//! par targets.
+
+use bevy::prelude::*;
+
+use crate::{LEVELS, save::SaveData};
+
+use super::{
+    chain::ChainStats,
+    hint::RevealedHints,
+    leaderboard::{LeaderboardEntry, PendingReplay},
+    level::{CurrentLevel, Level, LevelPar, PlacedLevelAtoms},
+    solution::SolutionShown,
+    state::GameState,
+    tick::ExperimentClock,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LevelScore>();
+    app.add_systems(OnEnter(GameState::LevelComplete), record_level_score);
+}
+
+/// How the most recently completed level went
+#[derive(Resource, Debug, Default, Clone)]
+pub struct LevelScore {
+    pub atoms_placed: u32,
+    pub ticks: u32,
+    pub reactions: u32,
+    pub hints_used: u32,
+    pub par: LevelPar,
+    pub stars: u8,
+}
+
+impl LevelPar {
+    /// Three stars for meeting every target, two for meeting some, and one for finishing at all
+    pub fn stars(&self, atoms_placed: u32, ticks: u32, reactions: u32, hints_used: u32) -> u8 {
+        let results = [
+            (self.atoms, atoms_placed),
+            (self.ticks, ticks),
+            (self.reactions, reactions),
+            (self.hints, hints_used),
+        ]
+        .into_iter()
+        .filter_map(|(par, value)| par.map(|par| value <= par));
+        let (met, total) = results.fold((0, 0), |(met, total), met_par| {
+            (met + u8::from(met_par), total + 1)
+        });
+        if met == total {
+            3
+        } else if met > 0 {
+            2
+        } else {
+            1
+        }
+    }
+}
+
+pub fn record_level_score(
+    mut score: ResMut<LevelScore>,
+    mut save_data: ResMut<SaveData>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+    placed_atoms: Res<PlacedLevelAtoms>,
+    clock: Res<ExperimentClock>,
+    chain_stats: Res<ChainStats>,
+    revealed_hints: Res<RevealedHints>,
+    solution_shown: Res<SolutionShown>,
+    pending_replay: Res<PendingReplay>,
+) -> Result {
+    let level = current_level.get_level(&level_assets)?;
+    let atoms_placed = placed_atoms.len() as u32;
+    let ticks = clock.tick();
+    let reactions = chain_stats.reactions;
+    let hints_used = if solution_shown.0 {
+        revealed_hints.0.max(level.hints.len()) as u32
+    } else {
+        revealed_hints.0 as u32
+    };
+    *score = LevelScore {
+        atoms_placed,
+        ticks,
+        reactions,
+        hints_used,
+        par: level.par.clone(),
+        stars: level.par.stars(atoms_placed, ticks, reactions, hints_used),
+    };
+
+    // Replays show how they scored, but don't count as the player completing the level
+    if pending_replay.is_playing() {
+        return Ok(());
+    }
+    if let Some(index) = current_level.get_index() {
+        let entry = LeaderboardEntry {
+            profile: save_data.profile_name().to_string(),
+            atoms_placed,
+            ticks,
+            reactions,
+            layout: placed_atoms.layout(),
+        };
+        let progress = save_data.level_mut(LEVELS[index]);
+        progress.completed = true;
+        progress.stars = progress.stars.max(score.stars);
+        progress.leaderboard.record(entry);
+        save_data.save();
+    }
+    Ok(())
+}
@@ -0,0 +1,326 @@
+//! Share codes: a level, and optionally a way to solve it, packed into a short piece of text that
+//! can be pasted into chat.
+//!
+//! A code is a version character followed by the level as compressed RON, in URL-safe base64 so
+//! that it can also be put in a link. Web builds copy a link to the game with the code in its
+//! `level` query parameter, which opens the level when followed.
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::level::{CurrentLevel, InvalidLevelError, Level, LevelPlacement, PlacedLevelAtoms};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Clipboard>();
+    app.add_event::<ShareCodeNotCopied>();
+    app.add_observer(copy_share_code);
+}
+
+/// Changes whenever the way levels are packed into codes changes
+const CODE_VERSION: char = '1';
+
+/// The name of the query parameter holding a share code in links to the web build
+pub const CODE_PARAMETER: &str = "level";
+
+/// Largest level a code may unpack to, so that a malicious code can't use up all the memory
+const MAX_LEVEL_SIZE: usize = 1 << 20;
+
+/// Highest compression level supported by `miniz_oxide`
+const COMPRESSION_LEVEL: u8 = 10;
+
+/// What a share code contains
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedLevel {
+    pub level: Level,
+    /// Atoms to place when the level is opened
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution: Option<Vec<LevelPlacement>>,
+}
+
+#[derive(Debug, Error)]
+pub enum ShareCodeError {
+    #[error("The code is empty")]
+    Empty,
+    #[error("The code is from a different version of the game")]
+    UnknownVersion,
+    #[error("The code contains characters that can't be in a code")]
+    Encoding(#[from] base64::DecodeError),
+    #[error("The code is incomplete")]
+    Compression,
+    #[error("The code doesn't contain a valid level: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("The code's level can't be played: {0}")]
+    Invalid(#[from] InvalidLevelError),
+}
+
+impl SharedLevel {
+    /// Pack the level into a share code
+    pub fn encode(&self) -> String {
+        let ron = ron::to_string(self).expect("Levels can always be written as RON");
+        let compressed = miniz_oxide::deflate::compress_to_vec(ron.as_bytes(), COMPRESSION_LEVEL);
+        format!("{CODE_VERSION}{}", URL_SAFE_NO_PAD.encode(compressed))
+    }
+
+    /// Unpack a share code. Links containing a code are accepted too.
+    pub fn decode(code: &str) -> Result<Self, ShareCodeError> {
+        let code = code.trim();
+        let code = code_from_link(code).unwrap_or(code);
+        let mut chars = code.chars();
+        match chars.next() {
+            None => return Err(ShareCodeError::Empty),
+            Some(CODE_VERSION) => {}
+            Some(_) => return Err(ShareCodeError::UnknownVersion),
+        }
+        let compressed = URL_SAFE_NO_PAD.decode(chars.as_str())?;
+        let ron = miniz_oxide::inflate::decompress_to_vec_with_limit(&compressed, MAX_LEVEL_SIZE)
+            .map_err(|_| ShareCodeError::Compression)?;
+        let shared: Self = ron::de::from_bytes(&ron)?;
+        shared.level.check_portals()?;
+        Ok(shared)
+    }
+}
+
+/// The share code in a link's query parameters, if it has one
+pub fn code_from_link(link: &str) -> Option<&str> {
+    let link = link.split('#').next().unwrap_or_default();
+    let (_, query) = link.split_once('?')?;
+    query.split('&').find_map(|parameter| {
+        parameter
+            .split_once('=')
+            .filter(|(name, _)| *name == CODE_PARAMETER)
+            .map(|(_, value)| value)
+    })
+}
+
+/// The share code the web build was opened with
+#[cfg(target_family = "wasm")]
+pub fn code_from_page_url() -> Option<String> {
+    let search = web_sys::window()?.location().search().ok()?;
+    code_from_link(&search).map(str::to_string)
+}
+
+/// The system clipboard. Native builds keep hold of it for as long as the game runs, because on
+/// Linux copied text is only available while the program that copied it is open.
+#[derive(Resource, Default)]
+pub struct Clipboard {
+    #[cfg(not(target_family = "wasm"))]
+    clipboard: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    #[cfg(not(target_family = "wasm"))]
+    fn system(&mut self) -> Result<&mut arboard::Clipboard, String> {
+        if self.clipboard.is_none() {
+            self.clipboard = Some(arboard::Clipboard::new().map_err(|err| err.to_string())?);
+        }
+        Ok(self
+            .clipboard
+            .as_mut()
+            .expect("The clipboard was just opened"))
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    pub fn set_text(&mut self, text: &str) -> Result<(), String> {
+        self.system()?.set_text(text).map_err(|err| err.to_string())
+    }
+
+    /// Browsers only allow pages to write to the clipboard, and only in response to the player
+    /// doing something
+    #[cfg(target_family = "wasm")]
+    pub fn set_text(&mut self, text: &str) -> Result<(), String> {
+        let window = web_sys::window().ok_or("There's no browser window")?;
+        // The promise is dropped, as there's nothing to do once the text has been copied
+        let _ = window.navigator().clipboard().write_text(text);
+        Ok(())
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    pub fn get_text(&mut self) -> Result<String, String> {
+        self.system()?.get_text().map_err(|err| err.to_string())
+    }
+
+    #[cfg(target_family = "wasm")]
+    pub fn get_text(&mut self) -> Result<String, String> {
+        Err("Paste the code with your browser instead".to_string())
+    }
+}
+
+/// Copy a share code for the current level to the clipboard
+#[derive(Event)]
+pub struct CopyShareCode {
+    /// Include the atoms the player has placed
+    pub with_solution: bool,
+}
+
+/// Sent with the text that should have been copied when the clipboard can't be written to, so
+/// that it can be shown to the player instead
+#[derive(Event)]
+pub struct ShareCodeNotCopied(pub String);
+
+fn copy_share_code(
+    trigger: Trigger<CopyShareCode>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+    placed_atoms: Res<PlacedLevelAtoms>,
+    mut clipboard: ResMut<Clipboard>,
+    mut not_copied: EventWriter<ShareCodeNotCopied>,
+) {
+    let Ok(level) = current_level.get_level(&level_assets) else {
+        warn!("There's no level to share");
+        return;
+    };
+    let code = SharedLevel {
+        level: level.clone(),
+        solution: trigger.with_solution.then(|| placed_atoms.placements()),
+    }
+    .encode();
+    let text = share_text(&code);
+    match clipboard.set_text(&text) {
+        Ok(()) => info!(
+            "Copied a share code to the clipboard ({} characters)",
+            text.len()
+        ),
+        Err(err) => {
+            warn!("Couldn't copy to the clipboard: {err}. The share code is {text}");
+            not_copied.write(ShareCodeNotCopied(text));
+        }
+    }
+}
+
+/// What gets copied to the clipboard: a link to the web build, or the code on its own
+#[cfg(target_family = "wasm")]
+fn share_text(code: &str) -> String {
+    let Some(location) = web_sys::window().map(|window| window.location()) else {
+        return code.to_string();
+    };
+    match (location.origin(), location.pathname()) {
+        (Ok(origin), Ok(path)) => format!("{origin}{path}?{CODE_PARAMETER}={code}"),
+        _ => code.to_string(),
+    }
+}
+
+#[cfg(not(target_family = "wasm"))]
+fn share_text(code: &str) -> String {
+    code.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        LEVELS,
+        game::{
+            atom::AtomType,
+            level::{LevelAtom, read_level_file},
+        },
+    };
+
+    fn campaign_level() -> Level {
+        let path = format!("{}/assets/levels/{}", env!("CARGO_MANIFEST_DIR"), LEVELS[0]);
+        read_level_file(path.as_ref()).unwrap()
+    }
+
+    /// A code for some compressed bytes, which needn't be a level
+    fn code_for(compressed: &[u8]) -> String {
+        format!("{CODE_VERSION}{}", URL_SAFE_NO_PAD.encode(compressed))
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        let level = campaign_level();
+        let shared = SharedLevel {
+            solution: level.solutions.first().cloned(),
+            level,
+        };
+        let code = shared.encode();
+        let decoded = SharedLevel::decode(&code).unwrap();
+        assert_eq!(
+            ron::to_string(&decoded).unwrap(),
+            ron::to_string(&shared).unwrap()
+        );
+        // Codes are pasted from chat, so may be surrounded by whitespace or be part of a link
+        let link = format!("https://example.com/game/?{CODE_PARAMETER}={code}");
+        for pasted in [format!("  {code}\n"), link] {
+            let decoded = SharedLevel::decode(&pasted).unwrap();
+            assert_eq!(
+                ron::to_string(&decoded).unwrap(),
+                ron::to_string(&shared).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn bad_codes_are_rejected() {
+        let code = SharedLevel {
+            level: campaign_level(),
+            solution: None,
+        }
+        .encode();
+        let compressed = URL_SAFE_NO_PAD.decode(&code[1..]).unwrap();
+
+        assert!(matches!(
+            SharedLevel::decode(""),
+            Err(ShareCodeError::Empty)
+        ));
+        assert!(matches!(
+            SharedLevel::decode(" \n"),
+            Err(ShareCodeError::Empty)
+        ));
+        assert!(matches!(
+            SharedLevel::decode(&format!("0{}", &code[1..])),
+            Err(ShareCodeError::UnknownVersion)
+        ));
+        assert!(matches!(
+            SharedLevel::decode(&format!("{CODE_VERSION}not*base64")),
+            Err(ShareCodeError::Encoding(_))
+        ));
+        assert!(matches!(
+            SharedLevel::decode(&code_for(&compressed[..compressed.len() / 2])),
+            Err(ShareCodeError::Compression)
+        ));
+        let not_a_level = miniz_oxide::deflate::compress_to_vec(b"(level: 3)", COMPRESSION_LEVEL);
+        assert!(matches!(
+            SharedLevel::decode(&code_for(&not_a_level)),
+            Err(ShareCodeError::Ron(_))
+        ));
+    }
+
+    #[test]
+    fn codes_with_bad_portals_are_rejected() {
+        let mut level = campaign_level();
+        level.atoms.extend(
+            [(0, 10), (1, 10), (2, 10)]
+                .map(|position| LevelAtom::new(AtomType::Portal(1), position)),
+        );
+        let code = SharedLevel {
+            level,
+            solution: None,
+        }
+        .encode();
+        assert!(matches!(
+            SharedLevel::decode(&code),
+            Err(ShareCodeError::Invalid(InvalidLevelError::TooManyPortals {
+                id: 1,
+                count: 3
+            }))
+        ));
+    }
+
+    #[test]
+    fn codes_are_found_in_links() {
+        assert_eq!(
+            code_from_link("https://example.com/game/?level=1abc"),
+            Some("1abc")
+        );
+        assert_eq!(
+            code_from_link("https://example.com/?lang=en&level=1abc&mute=1#play"),
+            Some("1abc")
+        );
+        assert_eq!(code_from_link("?level=1abc#play"), Some("1abc"));
+        assert_eq!(code_from_link("https://example.com/game/"), None);
+        assert_eq!(code_from_link("https://example.com/?levels=1abc"), None);
+        assert_eq!(code_from_link("https://example.com/#?level=1abc"), None);
+    }
+}
@@ -0,0 +1,976 @@
+//! A headless version of an experiment, for checking solutions without running the game.
+//!
+//! The simulation steps at a fixed rate and follows the same rules as the ECS systems: atoms
+//! move in straight lines, collide at grid intersections, react according to [`react`], and the
+//! level's goal is checked every step.
+
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+
+use super::{
+    atom::AtomType,
+    collision::ReactionEvent,
+    level::{Level, LevelGoal, PlacedAtom},
+    movement::CardinalDirection,
+    reaction::{Decay, Reactant, react},
+    tick::TICK_DURATION_SECS,
+};
+
+/// Length of a simulation step. Matches a 60fps frame.
+pub const STEP_SECS: f32 = 1.0 / 60.0;
+
+/// Experiments that haven't finished after this long are given up on
+pub const DEFAULT_TIME_LIMIT_SECS: f32 = 60.0;
+
+/// Version of the rules of the game. Bump this whenever a change could alter the outcome of an
+/// experiment, so that replays recorded under the old rules are refused.
+pub const SIMULATION_VERSION: u32 = 4;
+
+/// Atoms must be this close to a grid intersection to collide
+const COLLISION_EPSILON: f32 = 0.05;
+
+/// Atoms this close to a goal, detector or switch count as touching it
+const CONTACT_THRESHOLD: f32 = 0.2;
+
+/// How long newly created atoms ignore collisions for
+const COLLISION_COOLDOWN_SECS: f32 = 0.5;
+
+/// Moving atoms further than this from the origin will never come back, so are removed
+const BOUNDS: f32 = 40.0;
+
+/// Default speed of atoms, in grid cells per second
+const ATOM_SPEED: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimulationOutcome {
+    /// The level's goal was reached
+    Complete,
+    /// Nothing is moving and nothing else will happen
+    Stalled,
+    /// The time limit ran out
+    TimedOut,
+}
+
+/// The result of running a simulation to the end
+#[derive(Debug, Clone)]
+pub struct SimulationReport {
+    pub outcome: SimulationOutcome,
+    /// Ticks elapsed when the simulation ended, as counted by the experiment clock
+    pub ticks: u32,
+    pub reactions: Vec<ReactionEvent>,
+    /// Grid intersections that moving atoms passed through. These are the only places where a
+    /// stationary atom could change the outcome.
+    pub visited: HashSet<IVec2>,
+}
+
+impl SimulationReport {
+    pub fn is_complete(&self) -> bool {
+        self.outcome == SimulationOutcome::Complete
+    }
+}
+
+/// An atom in the simulation
+#[derive(Debug, Clone)]
+pub struct SimulatedAtom {
+    id: u32,
+    pub atom_type: AtomType,
+    pub position: Vec2,
+    pub direction: Option<CardinalDirection>,
+    speed: f32,
+    cooldown: f32,
+    /// Chain depth, or `None` for atoms that are part of the level
+    depth: Option<u32>,
+    decay: Option<(Decay, u32)>,
+    durability: Option<u32>,
+    /// Index of the gate this atom is standing in for, if it is a closed gate
+    gate: Option<usize>,
+}
+
+impl SimulatedAtom {
+    fn grid_position(&self) -> IVec2 {
+        self.position.round().as_ivec2()
+    }
+
+    /// Chain depth, or `None` for atoms that are part of the level
+    pub fn depth(&self) -> Option<u32> {
+        self.depth
+    }
+
+    /// Whether this is a closed gate rather than a real wall
+    pub fn is_gate(&self) -> bool {
+        self.gate.is_some()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SimulatedEmitter {
+    atom_type: AtomType,
+    position: IVec2,
+    direction: CardinalDirection,
+    interval: u32,
+    remaining: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct SimulatedDetector {
+    atom_type: AtomType,
+    position: IVec2,
+    direction: Option<CardinalDirection>,
+    target: Option<u32>,
+    counted: HashSet<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct SimulatedSwitch {
+    position: IVec2,
+    atom_type: Option<AtomType>,
+    gates: Vec<IVec2>,
+    triggered: HashSet<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct SimulatedGate {
+    position: IVec2,
+    open: bool,
+    /// Closed gates hit by antimatter are destroyed, just like walls
+    destroyed: bool,
+}
+
+/// The state of an experiment in progress
+#[derive(Debug, Clone)]
+pub struct Simulation<'a> {
+    level: &'a Level,
+    atoms: Vec<SimulatedAtom>,
+    goals: Vec<(AtomType, IVec2)>,
+    emitters: Vec<SimulatedEmitter>,
+    detectors: Vec<SimulatedDetector>,
+    switches: Vec<SimulatedSwitch>,
+    gates: Vec<SimulatedGate>,
+    next_id: u32,
+    time: f32,
+    tick_timer: f32,
+    tick: u32,
+    reactions: Vec<ReactionEvent>,
+    visited: HashSet<IVec2>,
+}
+
+impl<'a> Simulation<'a> {
+    /// Set up a level with the player's atoms placed, ready to run
+    pub fn new(level: &'a Level, placements: &[(IVec2, PlacedAtom)]) -> Self {
+        let mut simulation = Self {
+            level,
+            atoms: Vec::new(),
+            goals: Vec::new(),
+            emitters: Vec::new(),
+            detectors: Vec::new(),
+            switches: Vec::new(),
+            gates: Vec::new(),
+            next_id: 0,
+            time: 0.0,
+            tick_timer: 0.0,
+            tick: 0,
+            reactions: Vec::new(),
+            visited: HashSet::new(),
+        };
+        for level_atom in &level.atoms {
+            let atom = simulation.new_atom(
+                level_atom.atom_type,
+                level_atom.position.as_vec2(),
+                level_atom
+                    .velocity
+                    .as_ref()
+                    .map(|movement| movement.direction),
+                None,
+            );
+            atom.speed = level_atom
+                .velocity
+                .as_ref()
+                .map_or(ATOM_SPEED, |movement| movement.speed);
+            atom.decay = level_atom
+                .decay
+                .as_ref()
+                .map(|decay| (decay.clone(), decay.ticks));
+            atom.durability = level_atom.durability;
+        }
+        // Placed atoms enter the experiment in the same order as in the game
+        let mut placements = placements.to_vec();
+        placements.sort_by_key(|(position, _)| (position.x, position.y));
+        for (position, placed) in placements {
+            simulation.new_atom(
+                placed.atom_type,
+                position.as_vec2(),
+                placed.direction,
+                Some(0),
+            );
+        }
+        if let LevelGoal::ReachPositions(goals) = &level.goal {
+            simulation.goals = goals
+                .iter()
+                .map(|goal| (goal.atom_type, goal.position))
+                .collect();
+        }
+        simulation.emitters = level
+            .emitters
+            .iter()
+            .map(|emitter| SimulatedEmitter {
+                atom_type: emitter.atom_type,
+                position: emitter.position,
+                direction: emitter.direction,
+                interval: emitter.interval,
+                remaining: emitter.count,
+            })
+            .collect();
+        simulation.detectors = level
+            .detectors
+            .iter()
+            .map(|detector| SimulatedDetector {
+                atom_type: detector.atom_type,
+                position: detector.position,
+                direction: detector.direction,
+                target: detector.target,
+                counted: HashSet::new(),
+            })
+            .collect();
+        simulation.switches = level
+            .switches
+            .iter()
+            .map(|switch| SimulatedSwitch {
+                position: switch.position,
+                atom_type: switch.atom_type,
+                gates: switch.gates.clone(),
+                triggered: HashSet::new(),
+            })
+            .collect();
+        for (index, gate) in level.gates.iter().enumerate() {
+            simulation.gates.push(SimulatedGate {
+                position: gate.position,
+                open: gate.open,
+                destroyed: false,
+            });
+            if !gate.open {
+                simulation.close_gate(index);
+            }
+        }
+        simulation
+    }
+
+    fn new_atom(
+        &mut self,
+        atom_type: AtomType,
+        position: Vec2,
+        direction: Option<CardinalDirection>,
+        depth: Option<u32>,
+    ) -> &mut SimulatedAtom {
+        self.next_id += 1;
+        self.atoms.push(SimulatedAtom {
+            id: self.next_id,
+            atom_type,
+            position,
+            direction,
+            speed: ATOM_SPEED,
+            cooldown: 0.0,
+            depth,
+            decay: None,
+            durability: None,
+            gate: None,
+        });
+        self.atoms.last_mut().unwrap()
+    }
+
+    /// Spawn an atom created by a reaction, which ignores collisions for a short while
+    fn spawn_product(&mut self, product: Reactant, position: Vec2, depth: Option<u32>) {
+        self.spawn_product_with(product, None, true, position, depth);
+    }
+
+    /// Spawn a reaction product moving at `speed`, or the default speed if it's `None`
+    fn spawn_product_with(
+        &mut self,
+        (atom_type, direction): Reactant,
+        speed: Option<f32>,
+        cooldown: bool,
+        position: Vec2,
+        depth: Option<u32>,
+    ) {
+        let atom = self.new_atom(atom_type, position, direction, depth);
+        if let Some(speed) = speed {
+            atom.speed = speed;
+        }
+        if cooldown {
+            atom.cooldown = COLLISION_COOLDOWN_SECS;
+        }
+    }
+
+    fn close_gate(&mut self, index: usize) {
+        let position = self.gates[index].position.as_vec2();
+        let atom = self.new_atom(AtomType::Wall, position, None, None);
+        atom.gate = Some(index);
+    }
+
+    pub fn level(&self) -> &'a Level {
+        self.level
+    }
+
+    /// Atoms currently in the experiment, including closed gates, which act as walls
+    pub fn atoms(&self) -> &[SimulatedAtom] {
+        &self.atoms
+    }
+
+    /// Goal positions that haven't been reached yet
+    pub fn remaining_goals(&self) -> &[(AtomType, IVec2)] {
+        &self.goals
+    }
+
+    /// Ticks elapsed so far, as counted by the experiment clock
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Run until the level is complete, nothing more can happen, or the time limit is reached
+    pub fn run(mut self, time_limit_secs: f32) -> SimulationReport {
+        let outcome = loop {
+            if let Some(outcome) = self.step() {
+                break outcome;
+            }
+            if self.time >= time_limit_secs {
+                break SimulationOutcome::TimedOut;
+            }
+        };
+        SimulationReport {
+            outcome,
+            ticks: self.tick,
+            reactions: self.reactions,
+            visited: self.visited,
+        }
+    }
+
+    /// Advance by one step. Returns the outcome once the simulation has finished.
+    pub fn step(&mut self) -> Option<SimulationOutcome> {
+        self.time += STEP_SECS;
+        self.tick_timer += STEP_SECS;
+        let just_ticked = self.tick_timer >= TICK_DURATION_SECS;
+        if just_ticked {
+            self.tick_timer -= TICK_DURATION_SECS;
+            self.tick += 1;
+        }
+        for atom in &mut self.atoms {
+            atom.cooldown = (atom.cooldown - STEP_SECS).max(0.0);
+        }
+
+        self.move_atoms();
+        self.collide_atoms();
+        if just_ticked {
+            self.decay_atoms();
+            self.emit_atoms();
+        }
+        self.trigger_switches();
+        self.count_detected_atoms();
+        self.reach_goals();
+
+        self.atoms.retain(|atom| {
+            atom.direction.is_none()
+                || (atom.position.x.abs() < BOUNDS && atom.position.y.abs() < BOUNDS)
+        });
+
+        if self.goal_reached() {
+            Some(SimulationOutcome::Complete)
+        } else if self.is_stalled() {
+            Some(SimulationOutcome::Stalled)
+        } else {
+            None
+        }
+    }
+
+    fn move_atoms(&mut self) {
+        for atom in &mut self.atoms {
+            if let Some(direction) = atom.direction {
+                atom.position += direction.as_velocity() * atom.speed * STEP_SECS;
+            }
+        }
+    }
+
+    fn collide_atoms(&mut self) {
+        // Collisions are handled in the order they're found, so that results are reproducible
+        let mut collisions: Vec<(IVec2, Vec<usize>)> = Vec::new();
+        let mut collision_indices: HashMap<IVec2, usize> = HashMap::new();
+        let mut add_to_collision = |position: IVec2, index: usize| {
+            let collision = *collision_indices.entry(position).or_insert_with(|| {
+                collisions.push((position, Vec::new()));
+                collisions.len() - 1
+            });
+            collisions[collision].1.push(index);
+        };
+        let mut any_at_intersection = false;
+        for (index, atom) in self.atoms.iter().enumerate() {
+            if atom.direction.is_none() || atom.cooldown > 0.0 {
+                continue;
+            }
+            let nearest = atom.position.round();
+            if (atom.position - nearest).abs().max_element() <= COLLISION_EPSILON {
+                self.visited.insert(nearest.as_ivec2());
+                add_to_collision(nearest.as_ivec2(), index);
+                any_at_intersection = true;
+            }
+        }
+        if !any_at_intersection {
+            return;
+        }
+        for (index, atom) in self.atoms.iter().enumerate() {
+            if atom.direction.is_none() {
+                add_to_collision(atom.grid_position(), index);
+            }
+        }
+        collisions.retain(|(_, indices)| indices.len() > 1);
+
+        let mut destroyed = vec![false; self.atoms.len()];
+        for (position, indices) in collisions {
+            if indices.iter().any(|&index| destroyed[index]) {
+                continue;
+            }
+            if let Some(portal_id) =
+                indices
+                    .iter()
+                    .find_map(|&index| match self.atoms[index].atom_type {
+                        AtomType::Portal(id) => Some(id),
+                        _ => None,
+                    })
+            {
+                self.pass_through_portal(portal_id, position, &indices, &mut destroyed);
+                continue;
+            }
+
+            let reactants: Vec<Reactant> = indices
+                .iter()
+                .map(|&index| (self.atoms[index].atom_type, self.atoms[index].direction))
+                .collect();
+            let Some(outcome) = react(&reactants) else {
+                continue;
+            };
+
+            let depths: Vec<_> = indices
+                .iter()
+                .map(|&index| self.atoms[index].depth)
+                .collect();
+            // Atoms bouncing off walls keep their depth, as they haven't really reacted
+            let product_depth = match (&reactants[..], &depths[..]) {
+                ([(AtomType::Wall, _), _], [_, depth]) | ([_, (AtomType::Wall, _)], [depth, _]) => {
+                    *depth
+                }
+                _ => Some(
+                    depths
+                        .iter()
+                        .map(|depth| depth.unwrap_or(0))
+                        .max()
+                        .unwrap_or(0)
+                        + 1,
+                ),
+            };
+
+            for (&index, &is_destroyed) in indices.iter().zip(&outcome.destroyed) {
+                let atom = &mut self.atoms[index];
+                if is_destroyed {
+                    destroyed[index] = true;
+                    if let Some(gate) = atom.gate {
+                        self.gates[gate].destroyed = true;
+                    }
+                } else if atom.atom_type == AtomType::Wall
+                    && let Some(durability) = atom.durability.as_mut()
+                {
+                    *durability = durability.saturating_sub(1);
+                    if *durability == 0 {
+                        destroyed[index] = true;
+                    }
+                }
+            }
+            let speed = outcome
+                .keep_speed_of
+                .map(|index| self.atoms[indices[index]].speed);
+            for &product in &outcome.products {
+                self.spawn_product_with(
+                    product,
+                    speed,
+                    outcome.cooldown,
+                    position.as_vec2(),
+                    product_depth,
+                );
+            }
+            if outcome.bounce {
+                continue;
+            }
+            self.reactions.push(ReactionEvent {
+                tick: self.tick,
+                position,
+                inputs: reactants.iter().map(|(atom_type, _)| *atom_type).collect(),
+                outputs: outcome
+                    .products
+                    .iter()
+                    .map(|(atom_type, _)| *atom_type)
+                    .collect(),
+                depth: product_depth.unwrap_or(0),
+            });
+        }
+        self.remove_destroyed(&destroyed);
+    }
+
+    fn pass_through_portal(
+        &mut self,
+        portal_id: u8,
+        position: IVec2,
+        indices: &[usize],
+        destroyed: &mut [bool],
+    ) {
+        let Some(exit) = self.atoms.iter().find_map(|atom| {
+            (atom.atom_type == AtomType::Portal(portal_id) && atom.grid_position() != position)
+                .then_some(atom.grid_position())
+        }) else {
+            // Unlinked portals have no effect
+            return;
+        };
+        for &index in indices {
+            let atom = &self.atoms[index];
+            if atom.direction.is_some() {
+                destroyed[index] = true;
+                let (atom_type, direction, speed, depth) =
+                    (atom.atom_type, atom.direction, atom.speed, atom.depth);
+                let atom = self.new_atom(atom_type, exit.as_vec2(), direction, depth);
+                atom.speed = speed;
+                atom.cooldown = COLLISION_COOLDOWN_SECS;
+            }
+        }
+    }
+
+    fn remove_destroyed(&mut self, destroyed: &[bool]) {
+        let mut destroyed = destroyed.iter().chain(std::iter::repeat(&false));
+        self.atoms.retain(|_| !destroyed.next().unwrap());
+    }
+
+    fn decay_atoms(&mut self) {
+        let mut destroyed = vec![false; self.atoms.len()];
+        let mut products = Vec::new();
+        for (index, atom) in self.atoms.iter_mut().enumerate() {
+            let Some((decay, ticks_remaining)) = atom.decay.as_mut() else {
+                continue;
+            };
+            *ticks_remaining = ticks_remaining.saturating_sub(1);
+            if *ticks_remaining > 0 {
+                continue;
+            }
+            destroyed[index] = true;
+            let outputs = decay.product.products(atom.direction);
+            self.reactions.push(ReactionEvent {
+                tick: self.tick,
+                position: atom.grid_position(),
+                inputs: vec![atom.atom_type],
+                outputs: outputs.iter().map(|(atom_type, _)| *atom_type).collect(),
+                depth: atom.depth.unwrap_or(0),
+            });
+            products.extend(
+                outputs
+                    .into_iter()
+                    .map(|product| (product, atom.position, atom.depth)),
+            );
+        }
+        self.remove_destroyed(&destroyed);
+        for (product, position, depth) in products {
+            self.spawn_product(product, position, depth);
+        }
+    }
+
+    fn emit_atoms(&mut self) {
+        let tick = self.tick;
+        let mut emitted = Vec::new();
+        for emitter in &mut self.emitters {
+            if emitter.interval == 0 || !tick.is_multiple_of(emitter.interval) {
+                continue;
+            }
+            if let Some(remaining) = emitter.remaining.as_mut() {
+                if *remaining == 0 {
+                    continue;
+                }
+                *remaining -= 1;
+            }
+            emitted.push((emitter.atom_type, emitter.position, emitter.direction));
+        }
+        for (atom_type, position, direction) in emitted {
+            self.spawn_product((atom_type, Some(direction)), position.as_vec2(), None);
+        }
+    }
+
+    fn trigger_switches(&mut self) {
+        let mut toggled = Vec::new();
+        for switch in &mut self.switches {
+            for atom in &self.atoms {
+                if atom.gate.is_some()
+                    || atom.direction.is_none()
+                    || switch
+                        .atom_type
+                        .is_some_and(|atom_type| atom_type != atom.atom_type)
+                    || switch.triggered.contains(&atom.id)
+                {
+                    continue;
+                }
+                if atom.position.distance(switch.position.as_vec2()) < CONTACT_THRESHOLD {
+                    switch.triggered.insert(atom.id);
+                    toggled.extend(switch.gates.iter().copied());
+                }
+            }
+        }
+        for gate_position in toggled {
+            for index in 0..self.gates.len() {
+                let gate = &mut self.gates[index];
+                if gate.position != gate_position || gate.destroyed {
+                    continue;
+                }
+                gate.open = !gate.open;
+                if gate.open {
+                    self.atoms.retain(|atom| atom.gate != Some(index));
+                } else {
+                    self.close_gate(index);
+                }
+            }
+        }
+    }
+
+    fn count_detected_atoms(&mut self) {
+        for detector in &mut self.detectors {
+            for atom in &self.atoms {
+                if atom.atom_type != detector.atom_type
+                    || atom.direction.is_none()
+                    || detector.counted.contains(&atom.id)
+                {
+                    continue;
+                }
+                if detector.direction.is_some() && atom.direction != detector.direction {
+                    continue;
+                }
+                if atom.position.distance(detector.position.as_vec2()) < CONTACT_THRESHOLD {
+                    detector.counted.insert(atom.id);
+                }
+            }
+        }
+    }
+
+    fn reach_goals(&mut self) {
+        let mut destroyed = vec![false; self.atoms.len()];
+        self.goals.retain(|&(goal_type, goal_position)| {
+            let reached = self.atoms.iter().enumerate().find(|(index, atom)| {
+                !destroyed[*index]
+                    && atom.atom_type == goal_type
+                    && atom.position.distance(goal_position.as_vec2()) < CONTACT_THRESHOLD
+            });
+            if let Some((index, _)) = reached {
+                destroyed[index] = true;
+            }
+            reached.is_none()
+        });
+        self.remove_destroyed(&destroyed);
+    }
+
+    fn goal_reached(&self) -> bool {
+        match &self.level.goal {
+            LevelGoal::None => false,
+            LevelGoal::ReachPositions(_) => self.goals.is_empty(),
+            LevelGoal::CreateAtom(atom_type) => {
+                self.atoms.iter().any(|atom| atom.atom_type == *atom_type)
+            }
+            LevelGoal::FillDetectors => self.detectors.iter().all(|detector| {
+                detector
+                    .target
+                    .is_none_or(|target| detector.counted.len() as u32 >= target)
+            }),
+            LevelGoal::CreateAtomAtDepth { atom_type, depth } => self.atoms.iter().any(|atom| {
+                atom.atom_type == *atom_type
+                    && atom.depth.is_some_and(|atom_depth| atom_depth >= *depth)
+            }),
+        }
+    }
+
+    /// Nothing is moving, and no emitters or unstable atoms are left to change that
+    fn is_stalled(&self) -> bool {
+        self.atoms
+            .iter()
+            .all(|atom| atom.direction.is_none() && atom.decay.is_none())
+            && self
+                .emitters
+                .iter()
+                .all(|emitter| emitter.interval == 0 || emitter.remaining == Some(0))
+    }
+}
+
+/// Run a level with the given atoms placed, and report how it went
+pub fn simulate(level: &Level, placements: &[(IVec2, PlacedAtom)]) -> SimulationReport {
+    Simulation::new(level, placements).run(DEFAULT_TIME_LIMIT_SECS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        LEVELS,
+        game::{
+            MenuSelection,
+            board::render_level,
+            level::{LevelAtom, LevelPlacement, PlacedLevelAtoms},
+            state::GameState,
+            text_level::level_from_text,
+        },
+        game_state, headless_app, run_until,
+        screens::Screen,
+    };
+
+    fn load_level(file: &str) -> Level {
+        let path = format!("{}/assets/levels/{file}", env!("CARGO_MANIFEST_DIR"));
+        let contents = std::fs::read_to_string(&path).expect("Level file should be readable");
+        ron::from_str(&contents).unwrap_or_else(|error| panic!("Couldn't parse {file}: {error}"))
+    }
+
+    #[test]
+    fn level_solutions_complete_their_levels() {
+        for file in LEVELS {
+            let level = load_level(file);
+            assert!(!level.solutions.is_empty(), "{file} has no solutions");
+            for solution in &level.solutions {
+                let report = simulate(&level, &LevelPlacement::layout(solution));
+                assert_eq!(
+                    report.outcome,
+                    SimulationOutcome::Complete,
+                    "Solution for {file} didn't complete the level after {} ticks and {} reactions\n{}",
+                    report.ticks,
+                    report.reactions.len(),
+                    render_level(&level, solution)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn atoms_bounce_off_walls_at_the_same_speed() {
+        let mut atom = LevelAtom::new_with_velocity(AtomType::Basic, (0, 0), CardinalDirection::E);
+        atom.velocity.as_mut().unwrap().speed = 4.0;
+        let level = Level {
+            atoms: vec![atom, LevelAtom::new(AtomType::Wall, (2, 0))],
+            ..Default::default()
+        };
+        let mut simulation = Simulation::new(&level, &[]);
+        for _ in 0..60 {
+            simulation.step();
+            if let Some(atom) = simulation
+                .atoms()
+                .iter()
+                .find(|atom| atom.direction == Some(CardinalDirection::W))
+            {
+                assert_eq!(atom.atom_type, AtomType::Basic);
+                assert_eq!(atom.speed, 4.0);
+                return;
+            }
+        }
+        panic!("The atom didn't bounce off the wall");
+    }
+
+    /// How an experiment went, as far as the game and the simulation can both tell
+    #[derive(Debug, PartialEq)]
+    struct Playthrough {
+        complete: bool,
+        reactions: Vec<String>,
+    }
+
+    fn describe_reactions(reactions: &[ReactionEvent]) -> Vec<String> {
+        reactions
+            .iter()
+            .map(|reaction| format!("{} (depth {})", reaction.describe(), reaction.depth))
+            .collect()
+    }
+
+    /// Run a layout in the simulation, returning how it went and how many steps it took
+    fn play_in_simulation(level: &Level, layout: &[(IVec2, PlacedAtom)]) -> (Playthrough, u32) {
+        let mut simulation = Simulation::new(level, layout);
+        let mut steps = 0;
+        let outcome = loop {
+            steps += 1;
+            if let Some(outcome) = simulation.step() {
+                break outcome;
+            }
+            if simulation.time >= DEFAULT_TIME_LIMIT_SECS {
+                break SimulationOutcome::TimedOut;
+            }
+        };
+        let playthrough = Playthrough {
+            complete: outcome == SimulationOutcome::Complete,
+            reactions: describe_reactions(&simulation.reactions),
+        };
+        (playthrough, steps)
+    }
+
+    /// Run a layout in the game without a window, for at most `frames` frames
+    fn play_in_game(level: &Level, layout: &[(IVec2, PlacedAtom)], frames: u32) -> Playthrough {
+        let mut app = headless_app();
+        let handle = app
+            .world_mut()
+            .resource_mut::<Assets<Level>>()
+            .add(level.clone());
+        *app.world_mut().resource_mut::<MenuSelection>() = MenuSelection::Custom(handle);
+        app.world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Loading);
+        assert!(
+            run_until(&mut app, GameState::Placement),
+            "The level didn't load"
+        );
+        // Restarting the level spawns the placed atoms
+        app.world_mut()
+            .resource_mut::<PlacedLevelAtoms>()
+            .set_layout(layout);
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::RestartLevel);
+        assert!(run_until(&mut app, GameState::Placement));
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Running);
+        let mut reactions = Vec::new();
+        let mut complete = false;
+        for _ in 0..frames {
+            app.update();
+            reactions.extend(
+                app.world_mut()
+                    .resource_mut::<Events<ReactionEvent>>()
+                    .drain(),
+            );
+            if game_state(&app) == Some(GameState::LevelComplete) {
+                complete = true;
+                break;
+            }
+        }
+        Playthrough {
+            complete,
+            reactions: describe_reactions(&reactions),
+        }
+    }
+
+    fn assert_same_in_game(name: &str, level: &Level, placements: &[LevelPlacement]) {
+        let layout = LevelPlacement::layout(placements);
+        let (simulated, steps) = play_in_simulation(level, &layout);
+        // The game only shows the level as complete on the frame after the goal is reached
+        let frames = steps + u32::from(simulated.complete);
+        let played = play_in_game(level, &layout, frames);
+        assert_eq!(
+            played,
+            simulated,
+            "{name} went differently in the game\n{}",
+            render_level(level, placements)
+        );
+    }
+
+    fn text_level(text: &str) -> Level {
+        level_from_text(text).unwrap_or_else(|error| panic!("Couldn't read level: {error}"))
+    }
+
+    #[test]
+    fn level_solutions_play_the_same_in_the_game() {
+        for file in LEVELS {
+            let level = load_level(file);
+            for solution in &level.solutions {
+                assert_same_in_game(file, &level, solution);
+            }
+        }
+    }
+
+    /// Every rule the simulation copies from the game, on a small level of its own
+    #[test]
+    fn rules_play_the_same_in_the_game() {
+        let emitters_and_detectors = text_level(
+            r#"(
+    sidebar_text: "",
+    level_complete_text: "",
+    placeable_atoms: [Basic],
+    goal: FillDetectors,
+    legend: {
+        "E1": Emitter(atom_type: Basic, direction: E, interval: 2, count: Some(3)),
+        "D1": Detector(atom_type: Basic, direction: Some(E), target: Some(3)),
+    },
+)
+map: (0, 0) to (4, 0)
+E1. . . D1
+"#,
+        );
+        let switches_and_gates = text_level(
+            r#"(
+    sidebar_text: "",
+    level_complete_text: "",
+    placeable_atoms: [Basic],
+    legend: {
+        "*1": Switch(atom_type: None, gates: [(4, 0)]),
+    },
+)
+map: (0, 2) to (6, 0)
+A>. . = . . . 
+. . . . . . . 
+B>. *1. = . oB
+"#,
+        );
+        let portals = text_level(
+            r#"(
+    sidebar_text: "",
+    level_complete_text: "",
+    placeable_atoms: [Basic],
+)
+map: (0, 2) to (5, 0)
+. . . 0 . oB
+. . . . . . 
+B>. 0 . . . 
+"#,
+        );
+        let decay_and_durability = text_level(
+            r##"(
+    sidebar_text: "",
+    level_complete_text: "",
+    placeable_atoms: [Basic],
+    legend: {
+        "S1": Atom(atom_type: Splitting, velocity: Some((direction: E, speed: 2.0)), decay: Some((ticks: 1, product: Split(Basic)))),
+        "#1": Atom(atom_type: Wall, velocity: None, durability: Some(2)),
+    },
+)
+map: (0, 2) to (5, 0)
+S1. . . . . 
+. . . . . . 
+# B>. #1. oB
+"##,
+        );
+        let fusion = text_level(
+            r#"(
+    sidebar_text: "",
+    level_complete_text: "",
+    placeable_atoms: [Basic],
+)
+map: (0, 2) to (4, -2)
+. . . . . 
+. . . . . 
+. . . . # 
+. . . . . 
+. . # . . 
+"#,
+        );
+        // Placed in the opposite order to how they're sorted, so that the order the atoms are
+        // found in when they fuse can't come from the order they're listed in
+        let fusing_atoms = [
+            LevelPlacement {
+                atom_type: AtomType::Basic,
+                position: IVec2::new(2, 2),
+                direction: Some(CardinalDirection::S),
+            },
+            LevelPlacement {
+                atom_type: AtomType::Basic,
+                position: IVec2::new(0, 0),
+                direction: Some(CardinalDirection::E),
+            },
+        ];
+
+        assert_same_in_game("Emitters and detectors", &emitters_and_detectors, &[]);
+        assert_same_in_game("Switches and gates", &switches_and_gates, &[]);
+        assert_same_in_game("Portals", &portals, &[]);
+        assert_same_in_game("Decay and durability", &decay_and_durability, &[]);
+        assert_same_in_game("Fusion", &fusion, &fusing_atoms);
+    }
+}
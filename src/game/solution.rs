@@ -0,0 +1,179 @@
+//! Playback of a level's reference solution. The solution's atoms are shown as ghosts one at a
+//! time, and then placed and run as an experiment.
+
+use bevy::prelude::*;
+
+use crate::{LEVELS, save::SaveData, screens::Screen};
+
+use super::{
+    atom::AtomAssets,
+    hint::placement_ghost,
+    leaderboard::ReplayLayout,
+    level::{CurrentLevel, Level, LevelPlacement, PlacedLevelAtoms},
+    state::GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SolutionShown>();
+    app.add_observer(show_solution);
+    app.add_systems(
+        Update,
+        reset_solution_shown.run_if(resource_changed::<CurrentLevel>),
+    );
+    app.add_systems(
+        Update,
+        play_solution
+            .run_if(in_state(GameState::Placement).and(resource_exists::<SolutionPlayback>)),
+    );
+    // Starting the experiment by hand or leaving the level cancels the playback
+    app.add_systems(OnEnter(GameState::Running), cancel_playback);
+    app.add_systems(OnExit(Screen::Gameplay), cancel_playback);
+}
+
+/// Time between each ghost appearing, and before the experiment starts
+const GHOST_INTERVAL_SECS: f32 = 0.6;
+
+/// Whether the player has watched the current level's solution. Watching it counts as using
+/// every hint.
+#[derive(Resource, Default)]
+pub struct SolutionShown(pub bool);
+
+/// Play back the current level's first solution
+#[derive(Event)]
+pub struct ShowSolution;
+
+#[derive(Resource)]
+struct SolutionPlayback {
+    level_index: usize,
+    placements: Vec<LevelPlacement>,
+    shown: usize,
+    timer: Timer,
+}
+
+fn reset_solution_shown(mut shown: ResMut<SolutionShown>) {
+    shown.0 = false;
+}
+
+fn show_solution(
+    _: Trigger<ShowSolution>,
+    mut commands: Commands,
+    mut shown: ResMut<SolutionShown>,
+    mut save_data: ResMut<SaveData>,
+    mut placed_atoms: ResMut<PlacedLevelAtoms>,
+    mut next_state: ResMut<NextState<GameState>>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+) -> Result {
+    let level = current_level.get_level(&level_assets)?;
+    let (Some(level_index), Some(solution)) = (current_level.get_index(), level.solutions.first())
+    else {
+        return Ok(());
+    };
+    shown.0 = true;
+    let progress = save_data.level_mut(LEVELS[level_index]);
+    progress.hints_used = progress.hints_used.max(level.hints.len() as u32);
+    save_data.save();
+
+    commands.insert_resource(SolutionPlayback {
+        level_index,
+        placements: solution.clone(),
+        shown: 0,
+        timer: Timer::from_seconds(GHOST_INTERVAL_SECS, TimerMode::Repeating),
+    });
+    placed_atoms.clear();
+    next_state.set(GameState::RestartLevel);
+    Ok(())
+}
+
+fn play_solution(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut playback: ResMut<SolutionPlayback>,
+    atom_assets: Res<AtomAssets>,
+) {
+    if !playback.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    if let Some(placement) = playback.placements.get(playback.shown) {
+        commands.spawn((
+            Name::new("Solution Ghost"),
+            placement_ghost(placement, &atom_assets),
+            StateScoped(GameState::Placement),
+        ));
+        playback.shown += 1;
+    } else {
+        commands.trigger(ReplayLayout {
+            level_index: playback.level_index,
+            layout: LevelPlacement::layout(&playback.placements),
+        });
+        commands.remove_resource::<SolutionPlayback>();
+    }
+}
+
+fn cancel_playback(mut commands: Commands) {
+    commands.remove_resource::<SolutionPlayback>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        game::{MenuSelection, level::read_level_file},
+        game_state, headless_app, run_until,
+    };
+
+    fn load_solutions(file: &str) -> Vec<Vec<LevelPlacement>> {
+        let path = format!("{}/assets/levels/{file}", env!("CARGO_MANIFEST_DIR"));
+        read_level_file(path.as_ref())
+            .unwrap_or_else(|error| panic!("Couldn't read {file}: {error}"))
+            .solutions
+    }
+
+    #[test]
+    fn level_solutions_complete_their_levels_in_game() {
+        for (level_index, file) in LEVELS.iter().enumerate() {
+            for solution in load_solutions(file) {
+                let mut app = headless_app();
+                app.update();
+                app.world_mut().trigger(ReplayLayout {
+                    level_index,
+                    layout: LevelPlacement::layout(&solution),
+                });
+                assert!(
+                    run_until(&mut app, GameState::LevelComplete),
+                    "Solution for {file} didn't complete the level, ending in {:?}",
+                    game_state(&app)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn showing_the_solution_plays_it_without_completing_the_level() {
+        let mut app = headless_app();
+        *app.world_mut().resource_mut::<MenuSelection>() = MenuSelection::Level(0);
+        app.world_mut()
+            .resource_mut::<NextState<Screen>>()
+            .set(Screen::Loading);
+        assert!(run_until(&mut app, GameState::Placement));
+
+        app.world_mut().trigger(ShowSolution);
+        assert!(
+            run_until(&mut app, GameState::LevelComplete),
+            "The solution didn't complete the level, ending in {:?}",
+            game_state(&app)
+        );
+        let save_data = app.world().resource::<SaveData>();
+        let progress = save_data.level(LEVELS[0]);
+        assert!(progress.is_none_or(|progress| !progress.completed
+            && progress.stars == 0
+            && progress.leaderboard.entries.is_empty()));
+        // Nor does it count towards the player's statistics or achievements
+        let statistics = &save_data.statistics;
+        assert_eq!(statistics.experiments_run, 0);
+        assert_eq!(statistics.levels_completed, 0);
+        assert_eq!(statistics.total_reactions(), 0);
+        assert!(save_data.achievements.unlocked.is_empty());
+        assert!(save_data.achievements.produced.is_empty());
+    }
+}
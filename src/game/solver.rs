@@ -0,0 +1,452 @@
+//! Searches for ways to complete a level by simulating candidate placements without a window.
+//!
+//! Placements are tried in order of how many atoms they use, so the first solution found uses as
+//! few atoms as possible. A stationary atom can only change the outcome if something passes
+//! through its cell, so each layout is only extended with atoms on the cells that its own
+//! experiment visited.
+
+use bevy::{
+    platform::collections::HashSet,
+    prelude::*,
+    tasks::{AsyncComputeTaskPool, Task, block_on, poll_once},
+};
+
+use super::{
+    analysis::find_solutions,
+    level::{CurrentLevel, Level, LevelGoal, LevelPlacement, PlacedAtom},
+    movement::CardinalDirection,
+    simulation::{SimulationReport, simulate},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SolverStatus>();
+    app.add_observer(check_solvable);
+    app.add_observer(find_all_solutions);
+    app.add_systems(
+        Update,
+        (
+            poll_solver_task.run_if(resource_exists::<SolverTask>),
+            reset_solver_status.run_if(resource_changed::<CurrentLevel>),
+        ),
+    );
+}
+
+/// Most atoms to try placing when a level doesn't suggest a limit itself
+const DEFAULT_MAX_ATOMS: usize = 4;
+
+/// How far beyond the level's contents placements are tried
+const REGION_MARGIN: i32 = 2;
+
+/// Cells visible on screen at the default zoom. Placements are always tried anywhere in here.
+const VISIBLE_REGION: IRect = IRect {
+    min: IVec2::new(-7, -4),
+    max: IVec2::new(7, 4),
+};
+
+/// Most experiments to simulate before giving up
+#[cfg(not(target_family = "wasm"))]
+const DEFAULT_MAX_SIMULATIONS: usize = 20_000;
+
+/// Web builds run searches on the main thread, so stop much sooner to keep the game from freezing
+/// for more than a moment
+#[cfg(target_family = "wasm")]
+const DEFAULT_MAX_SIMULATIONS: usize = 500;
+
+/// Bounds on how hard the solver searches
+#[derive(Debug, Clone)]
+pub struct SolverLimits {
+    pub max_atoms: usize,
+    /// Grid cells that atoms may be placed in
+    pub region: IRect,
+    pub max_simulations: usize,
+}
+
+impl SolverLimits {
+    /// Limits suited to a level. Up to as many atoms as the level's par (or its longest known
+    /// solution) are tried, anywhere on screen or near the level's contents.
+    pub fn for_level(level: &Level) -> Self {
+        let max_atoms = level
+            .par
+            .atoms
+            .map(|atoms| atoms as usize)
+            .or_else(|| level.solutions.iter().map(Vec::len).max())
+            .unwrap_or(DEFAULT_MAX_ATOMS);
+        Self {
+            max_atoms,
+            region: level_bounds(level)
+                .inflate(REGION_MARGIN)
+                .union(VISIBLE_REGION),
+            max_simulations: DEFAULT_MAX_SIMULATIONS,
+        }
+    }
+}
+
+/// Positions of everything in the level. Atoms can't be placed on any of these.
+pub fn level_positions(level: &Level) -> Vec<IVec2> {
+    let goals = match &level.goal {
+        LevelGoal::ReachPositions(goals) => goals.iter().map(|goal| goal.position).collect(),
+        _ => Vec::new(),
+    };
+    level
+        .atoms
+        .iter()
+        .map(|atom| atom.position)
+        .chain(goals)
+        .chain(level.emitters.iter().map(|emitter| emitter.position))
+        .chain(level.detectors.iter().map(|detector| detector.position))
+        .chain(level.switches.iter().map(|switch| switch.position))
+        .chain(level.gates.iter().map(|gate| gate.position))
+        .collect()
+}
+
+/// The smallest rectangle containing everything in the level
+pub fn level_bounds(level: &Level) -> IRect {
+    level_positions(level)
+        .into_iter()
+        .fold(IRect::EMPTY, |bounds, position| {
+            bounds.union_point(position)
+        })
+}
+
+#[derive(Debug, Clone)]
+pub enum SolveResult {
+    /// A solution using as few atoms as possible, with the ticks it takes and reactions it causes
+    Solved {
+        solution: Vec<LevelPlacement>,
+        ticks: u32,
+        reactions: usize,
+    },
+    /// Every placement within the limits was tried, and none of them complete the level
+    Unsolvable,
+    /// The search ran out of simulations before trying every placement
+    GaveUp,
+    /// The level has no goal, so can't be completed
+    NoGoal,
+}
+
+#[derive(Debug, Clone)]
+pub struct SolveReport {
+    pub result: SolveResult,
+    /// Number of experiments simulated during the search
+    pub simulations: usize,
+}
+
+impl SolveReport {
+    /// A one line summary of the result
+    pub fn describe(&self, limits: &SolverLimits) -> String {
+        match &self.result {
+            SolveResult::Solved {
+                solution,
+                ticks,
+                reactions,
+            } => format!(
+                "Solvable with {} atom{} ({ticks} ticks, {reactions} reactions)",
+                solution.len(),
+                if solution.len() == 1 { "" } else { "s" }
+            ),
+            SolveResult::Unsolvable => {
+                format!("No solution with up to {} atoms", limits.max_atoms)
+            }
+            SolveResult::GaveUp => {
+                format!("Gave up after {} experiments", self.simulations)
+            }
+            SolveResult::NoGoal => "Level has no goal".to_string(),
+        }
+    }
+}
+
+type Layout = Vec<(IVec2, PlacedAtom)>;
+
+/// Search for the solution to a level that uses the fewest atoms
+pub fn solve(level: &Level, limits: &SolverLimits) -> SolveReport {
+    if matches!(level.goal, LevelGoal::None) {
+        return SolveReport {
+            result: SolveResult::NoGoal,
+            simulations: 0,
+        };
+    }
+    let mut searched = 0;
+    let mut found = None;
+    let exhausted = search(level, limits, |layout, report| {
+        searched += 1;
+        if report.is_complete() {
+            found = Some((layout.to_vec(), report.ticks, report.reactions.len()));
+            return SearchControl::Stop;
+        }
+        SearchControl::Continue
+    });
+    let result = match found {
+        Some((layout, ticks, reactions)) => SolveResult::Solved {
+            solution: layout
+                .into_iter()
+                .map(|(position, placed)| LevelPlacement {
+                    atom_type: placed.atom_type,
+                    position,
+                    direction: placed.direction,
+                })
+                .collect(),
+            ticks,
+            reactions,
+        },
+        None if exhausted => SolveResult::Unsolvable,
+        None => SolveResult::GaveUp,
+    };
+    SolveReport {
+        result,
+        simulations: searched,
+    }
+}
+
+/// What the search should do after simulating a layout
+pub enum SearchControl {
+    Continue,
+    Stop,
+}
+
+/// Simulate every layout within the limits, fewest atoms first, passing each one to `visit`.
+/// Layouts that complete the level aren't extended with more atoms. Returns whether every layout
+/// was tried.
+pub fn search(
+    level: &Level,
+    limits: &SolverLimits,
+    mut visit: impl FnMut(&[(IVec2, PlacedAtom)], &SimulationReport) -> SearchControl,
+) -> bool {
+    let occupied: HashSet<IVec2> = level_positions(level).into_iter().collect();
+    let mut seen: HashSet<Layout> = HashSet::new();
+    let mut frontier: Vec<(Layout, Vec<IVec2>)> = vec![(Vec::new(), Vec::new())];
+    let mut simulations = 0;
+
+    for atom_count in 0..=limits.max_atoms {
+        let mut next_frontier = Vec::new();
+        for (layout, visited) in &frontier {
+            let candidates = if atom_count == 0 {
+                vec![layout.clone()]
+            } else {
+                extensions(level, limits, &occupied, layout, visited)
+            };
+            for candidate in candidates {
+                if !seen.insert(candidate.clone()) {
+                    continue;
+                }
+                if simulations >= limits.max_simulations {
+                    return false;
+                }
+                simulations += 1;
+                let report = simulate(level, &candidate);
+                if let SearchControl::Stop = visit(&candidate, &report) {
+                    return false;
+                }
+                if !report.is_complete() && atom_count < limits.max_atoms {
+                    let mut visited: Vec<_> = report.visited.into_iter().collect();
+                    visited.sort_by_key(|position| (position.x, position.y));
+                    next_frontier.push((candidate, visited));
+                }
+            }
+        }
+        if next_frontier.is_empty() {
+            break;
+        }
+        frontier = next_frontier;
+    }
+    true
+}
+
+/// Every layout made by adding one more atom to `layout`
+fn extensions(
+    level: &Level,
+    limits: &SolverLimits,
+    occupied: &HashSet<IVec2>,
+    layout: &[(IVec2, PlacedAtom)],
+    visited: &[IVec2],
+) -> Vec<Layout> {
+    let is_free = |position: &IVec2| {
+        limits.region.contains(*position)
+            && !occupied.contains(position)
+            && !layout.iter().any(|(placed, _)| placed == position)
+    };
+    let mut placements: Vec<(IVec2, PlacedAtom)> = Vec::new();
+    for &position in visited.iter().filter(|position| is_free(position)) {
+        for &atom_type in &level.placeable_atoms {
+            placements.push((
+                position,
+                PlacedAtom {
+                    atom_type,
+                    direction: None,
+                },
+            ));
+        }
+    }
+    if level.allow_moving_placement {
+        // Moving atoms change the outcome wherever they start, so try them in every free cell
+        for x in limits.region.min.x..=limits.region.max.x {
+            for y in limits.region.min.y..=limits.region.max.y {
+                let position = IVec2::new(x, y);
+                if !is_free(&position) {
+                    continue;
+                }
+                for &atom_type in &level.placeable_atoms {
+                    for direction in CardinalDirection::ALL {
+                        placements.push((
+                            position,
+                            PlacedAtom {
+                                atom_type,
+                                direction: Some(direction),
+                            },
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    placements
+        .into_iter()
+        .map(|placement| {
+            let mut extended = layout.to_vec();
+            extended.push(placement);
+            extended.sort_by_key(|(position, _)| (position.x, position.y));
+            extended
+        })
+        .collect()
+}
+
+/// Check whether the current level can be completed
+#[derive(Event)]
+pub struct CheckSolvable;
+
+/// Find every way to complete the current level, to compare against its reference solution
+#[derive(Event)]
+pub struct FindAllSolutions;
+
+/// Progress of the most recent request to the solver
+#[derive(Resource, Default)]
+pub enum SolverStatus {
+    #[default]
+    Idle,
+    Running,
+    Finished(String),
+}
+
+#[derive(Resource)]
+struct SolverTask(Task<String>);
+
+fn check_solvable(
+    _: Trigger<CheckSolvable>,
+    commands: Commands,
+    status: ResMut<SolverStatus>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+) -> Result {
+    let level = current_level.get_level(&level_assets)?.clone();
+    start_solver_task(commands, status, move || {
+        let limits = SolverLimits::for_level(&level);
+        solve(&level, &limits).describe(&limits)
+    });
+    Ok(())
+}
+
+fn find_all_solutions(
+    _: Trigger<FindAllSolutions>,
+    commands: Commands,
+    status: ResMut<SolverStatus>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+) -> Result {
+    let level = current_level.get_level(&level_assets)?.clone();
+    start_solver_task(commands, status, move || {
+        find_solutions(&level, &SolverLimits::for_level(&level)).describe()
+    });
+    Ok(())
+}
+
+/// Run a search in the background, unless one is already running. The search returns a
+/// description of its result.
+fn start_solver_task(
+    mut commands: Commands,
+    mut status: ResMut<SolverStatus>,
+    search: impl FnOnce() -> String + Send + 'static,
+) {
+    if matches!(*status, SolverStatus::Running) {
+        return;
+    }
+    let task = AsyncComputeTaskPool::get().spawn(async move { search() });
+    commands.insert_resource(SolverTask(task));
+    *status = SolverStatus::Running;
+}
+
+fn poll_solver_task(
+    mut commands: Commands,
+    mut task: ResMut<SolverTask>,
+    mut status: ResMut<SolverStatus>,
+) {
+    if let Some(description) = block_on(poll_once(&mut task.0)) {
+        *status = SolverStatus::Finished(description);
+        commands.remove_resource::<SolverTask>();
+    }
+}
+
+/// Results are out of date once the level changes
+fn reset_solver_status(mut commands: Commands, mut status: ResMut<SolverStatus>) {
+    commands.remove_resource::<SolverTask>();
+    *status = SolverStatus::Idle;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        LEVELS,
+        game::{
+            atom::AtomType,
+            level::{LevelAtom, LevelGoalPosition, read_level_file},
+        },
+    };
+
+    #[test]
+    fn campaign_levels_are_solved_within_par() {
+        // Levels with small pars, so that the search is quick
+        for file in [LEVELS[0], LEVELS[1], LEVELS[7]] {
+            let path = format!("{}/assets/levels/{file}", env!("CARGO_MANIFEST_DIR"));
+            let level = read_level_file(path.as_ref()).unwrap();
+            let par = level.par.atoms.expect("Campaign levels have an atom par") as usize;
+            let report = solve(&level, &SolverLimits::for_level(&level));
+            let SolveResult::Solved { solution, .. } = report.result else {
+                panic!("{file} wasn't solved: {:?}", report.result);
+            };
+            assert!(
+                solution.len() <= par,
+                "{file} was solved with {} atoms, but its par is {par}",
+                solution.len()
+            );
+            assert!(simulate(&level, &LevelPlacement::layout(&solution)).is_complete());
+        }
+    }
+
+    #[test]
+    fn impossible_levels_are_unsolvable() {
+        // Nothing placed in the atom's path can turn it north towards the goal
+        let level = Level {
+            atoms: vec![LevelAtom::new_with_velocity(
+                AtomType::Basic,
+                (-3, 0),
+                CardinalDirection::E,
+            )],
+            goal: LevelGoal::ReachPositions(vec![LevelGoalPosition {
+                atom_type: AtomType::Basic,
+                position: IVec2::new(0, 3),
+            }]),
+            placeable_atoms: vec![AtomType::Basic],
+            ..default()
+        };
+        let limits = SolverLimits {
+            max_atoms: 1,
+            ..SolverLimits::for_level(&level)
+        };
+        let report = solve(&level, &limits);
+        assert!(
+            matches!(report.result, SolveResult::Unsolvable),
+            "{:?}",
+            report.result
+        );
+        assert!(report.simulations < limits.max_simulations);
+    }
+}
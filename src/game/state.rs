@@ -22,6 +22,37 @@ pub(super) fn plugin(app: &mut App) {
             .run_if(in_state(Screen::Gameplay)),
     );
     app.add_systems(OnEnter(GameState::RestartLevel), handle_restart);
+    app.configure_sets(
+        Update,
+        (
+            ExperimentSystems::Move,
+            ExperimentSystems::React,
+            ExperimentSystems::Emit,
+            ExperimentSystems::Trigger,
+            ExperimentSystems::Detect,
+            ExperimentSystems::Goal,
+        )
+            .chain()
+            .in_set(AppSystems::Update),
+    );
+}
+
+/// The order the rules of a running experiment are applied in each frame, which matches a step
+/// of the [`Simulation`](super::simulation::Simulation).
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum ExperimentSystems {
+    /// Move atoms along their paths
+    Move,
+    /// Collide atoms and decay unstable ones
+    React,
+    /// Launch atoms from emitters
+    Emit,
+    /// Toggle gates from switches
+    Trigger,
+    /// Count atoms passing detectors
+    Detect,
+    /// Check whether atoms reached the goal
+    Goal,
 }
 
 fn handle_restart(mut next_state: ResMut<NextState<GameState>>) {
@@ -0,0 +1,121 @@
+//! Lifetime statistics about everything the player has done, kept in the save file.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{Pause, save::SaveData, screens::Screen};
+
+use super::{
+    collision::ReactionEvent, leaderboard::is_replaying, placement::AtomPlaced, state::GameState,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(count_placed_atom);
+    // Replays are the game playing, not the player, so they don't count
+    app.add_systems(
+        OnEnter(GameState::Running),
+        count_experiment.run_if(not(is_replaying)),
+    );
+    app.add_systems(
+        OnEnter(GameState::RestartLevel),
+        count_reset.run_if(not(is_replaying)),
+    );
+    app.add_systems(
+        OnEnter(GameState::LevelComplete),
+        count_level_complete.run_if(not(is_replaying)),
+    );
+    app.add_systems(
+        Update,
+        (
+            count_reactions.run_if(in_state(GameState::Running).and(not(is_replaying))),
+            count_play_time.run_if(in_state(Pause(false))),
+        )
+            .run_if(in_state(Screen::Gameplay)),
+    );
+    // Most counters change too often to write out every time, so save at natural breaks
+    app.add_systems(OnExit(GameState::Running), save_statistics);
+    app.add_systems(OnExit(Screen::Gameplay), save_statistics);
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Statistics {
+    /// Times the player has started an experiment
+    #[serde(default)]
+    pub experiments_run: u32,
+    /// Reactions that have happened, keyed by the atoms involved, e.g. "Basic + Splitting"
+    #[serde(default)]
+    pub reactions: BTreeMap<String, u32>,
+    /// Atoms placed by the player, keyed by atom type
+    #[serde(default)]
+    pub atoms_placed: BTreeMap<String, u32>,
+    #[serde(default)]
+    pub levels_completed: u32,
+    /// Time spent in gameplay, not counting while paused
+    #[serde(default)]
+    pub play_time_secs: f64,
+    /// Times the player has cleared a level and started again
+    #[serde(default)]
+    pub resets: u32,
+}
+
+impl Statistics {
+    pub fn total_reactions(&self) -> u32 {
+        self.reactions.values().sum()
+    }
+
+    pub fn total_atoms_placed(&self) -> u32 {
+        self.atoms_placed.values().sum()
+    }
+}
+
+fn count_placed_atom(trigger: Trigger<AtomPlaced>, mut save_data: ResMut<SaveData>) {
+    let atom_type = trigger.event().0;
+    *save_data
+        .statistics
+        .atoms_placed
+        .entry(format!("{atom_type:?}"))
+        .or_default() += 1;
+}
+
+fn count_experiment(mut save_data: ResMut<SaveData>) {
+    save_data.statistics.experiments_run += 1;
+}
+
+fn count_reset(mut save_data: ResMut<SaveData>) {
+    save_data.statistics.resets += 1;
+    save_data.save();
+}
+
+fn count_level_complete(mut save_data: ResMut<SaveData>) {
+    save_data.statistics.levels_completed += 1;
+}
+
+fn count_reactions(mut reactions: EventReader<ReactionEvent>, mut save_data: ResMut<SaveData>) {
+    if reactions.is_empty() {
+        return;
+    }
+    for reaction in reactions.read() {
+        let mut inputs: Vec<_> = reaction
+            .inputs
+            .iter()
+            .map(|atom_type| format!("{atom_type:?}"))
+            .collect();
+        // Order doesn't matter, so "Splitting + Basic" is counted as "Basic + Splitting"
+        inputs.sort();
+        *save_data
+            .statistics
+            .reactions
+            .entry(inputs.join(" + "))
+            .or_default() += 1;
+    }
+}
+
+fn count_play_time(time: Res<Time>, mut save_data: ResMut<SaveData>) {
+    save_data.statistics.play_time_secs += time.delta_secs_f64();
+}
+
+fn save_statistics(save_data: Res<SaveData>) {
+    save_data.save();
+}
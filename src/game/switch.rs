@@ -0,0 +1,173 @@
+use bevy::{
+    ecs::spawn::SpawnIter, platform::collections::HashSet, prelude::*, render::view::RenderLayers,
+};
+
+use crate::{
+    AppSystems, PausableSystems,
+    audio::{AudioAssets, sound_effect},
+    screens::Screen,
+};
+
+use super::{
+    atom::{AtomAssets, AtomType},
+    level::{LevelGate, LevelSwitch},
+    movement::Movement,
+    placement::GridPos,
+    state::{ExperimentSystems, GameState},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        trigger_switches
+            .run_if(in_state(GameState::Running))
+            .in_set(AppSystems::Update)
+            .in_set(ExperimentSystems::Trigger)
+            .in_set(PausableSystems),
+    );
+    app.add_systems(
+        Update,
+        draw_switch_links.run_if(in_state(GameState::Placement)),
+    );
+}
+
+/// A stationary cell that toggles its linked gates whenever an atom passes over it
+#[derive(Component)]
+pub struct Switch {
+    /// Only atoms of this type trigger the switch
+    pub atom_type: Option<AtomType>,
+    /// Positions of the gates toggled by this switch
+    pub gates: Vec<IVec2>,
+    /// Atoms that have already triggered the switch, so that an atom only triggers it once per
+    /// pass
+    triggered: HashSet<Entity>,
+}
+
+/// A cell that acts as a wall while closed, and lets atoms through while open
+#[derive(Component)]
+pub struct Gate {
+    pub open: bool,
+}
+
+impl Gate {
+    fn color(&self) -> Color {
+        if self.open {
+            Color::srgba(0.6, 0.7, 1.0, 0.25)
+        } else {
+            Color::srgb(0.6, 0.7, 1.0)
+        }
+    }
+}
+
+pub fn switch(level_switch: &LevelSwitch, atom_assets: &AtomAssets) -> impl Bundle {
+    let position = level_switch.position;
+    let atom_assets = atom_assets.clone();
+    (
+        Name::new("Switch"),
+        Sprite {
+            image: atom_assets.circle.clone(),
+            color: Color::srgba(0.9, 0.7, 0.2, 0.6),
+            custom_size: Some(Vec2::splat(0.6)),
+            ..Default::default()
+        },
+        Switch {
+            atom_type: level_switch.atom_type,
+            gates: level_switch.gates.clone(),
+            triggered: HashSet::new(),
+        },
+        GridPos(position),
+        Transform::from_xyz(position.x as f32, position.y as f32, 0.0),
+        RenderLayers::layer(2),
+        StateScoped(Screen::Gameplay),
+        // Show which atom type triggers the switch, if it is restricted to one
+        Children::spawn(SpawnIter(level_switch.atom_type.into_iter().map(
+            move |atom_type| {
+                (
+                    atom_type.get_ghost_sprite(&atom_assets),
+                    Transform::from_xyz(0.0, 0.0, 1.0).with_scale(Vec3::splat(0.0012)),
+                    RenderLayers::layer(2),
+                )
+            },
+        ))),
+    )
+}
+
+pub fn gate(level_gate: &LevelGate, atom_assets: &AtomAssets) -> impl Bundle {
+    let position = level_gate.position;
+    let gate = Gate {
+        open: level_gate.open,
+    };
+    (
+        Name::new("Gate"),
+        Sprite {
+            image: atom_assets.wall.clone(),
+            color: gate.color(),
+            ..Default::default()
+        },
+        gate,
+        GridPos(position),
+        Transform::from_xyz(position.x as f32, position.y as f32, 0.0)
+            .with_scale(Vec3::splat(0.002)),
+        RenderLayers::layer(2),
+        StateScoped(Screen::Gameplay),
+    )
+}
+
+/// Closed gates are treated as walls by the collision system
+pub fn set_gate_collision(entity: &mut EntityCommands, open: bool) {
+    if open {
+        entity.remove::<AtomType>();
+    } else {
+        entity.insert(AtomType::Wall);
+    }
+}
+
+fn trigger_switches(
+    mut commands: Commands,
+    mut switches: Query<(&Transform, &mut Switch)>,
+    mut gates: Query<(Entity, &GridPos, &mut Gate, &mut Sprite)>,
+    // Only moving atoms press switches, so a stationary atom placed on one does nothing
+    atoms: Query<(Entity, &Transform, &AtomType), (With<Movement>, Without<Gate>)>,
+    audio_assets: Res<AudioAssets>,
+) {
+    for (switch_transform, mut switch) in &mut switches {
+        for (atom_entity, atom_transform, atom_type) in &atoms {
+            if switch
+                .atom_type
+                .is_some_and(|switch_type| switch_type != *atom_type)
+                || switch.triggered.contains(&atom_entity)
+            {
+                continue;
+            }
+            let distance = switch_transform
+                .translation
+                .distance(atom_transform.translation);
+            let trigger_threshold = 0.2;
+
+            if distance < trigger_threshold {
+                switch.triggered.insert(atom_entity);
+                for (gate_entity, grid_pos, mut gate, mut sprite) in &mut gates {
+                    if switch.gates.contains(&grid_pos.0) {
+                        gate.open = !gate.open;
+                        sprite.color = gate.color();
+                        set_gate_collision(&mut commands.entity(gate_entity), gate.open);
+                    }
+                }
+
+                commands.spawn(sound_effect(audio_assets.hit_sfx.clone()));
+            }
+        }
+    }
+}
+
+fn draw_switch_links(mut gizmos: Gizmos, switches: Query<(&Switch, &GridPos)>) {
+    for (switch, grid_pos) in &switches {
+        for gate_position in &switch.gates {
+            gizmos.line_2d(
+                grid_pos.0.as_vec2(),
+                gate_position.as_vec2(),
+                LinearRgba::rgb(0.9, 0.7, 0.2),
+            );
+        }
+    }
+}
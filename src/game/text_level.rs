@@ -0,0 +1,587 @@
+//! Text levels: an alternative to writing levels as RON, where everything in the level is drawn
+//! on a map instead of being listed with its position. A text level is a RON header holding the
+//! rest of the level, followed by the map, in the same format as a [`Board`]:
+//!
+//! ```text
+//! (
+//!     sidebar_text: "Hit the wall",
+//!     level_complete_text: "Well done!",
+//!     placeable_atoms: [Basic],
+//!     par: (atoms: Some(1)),
+//!     legend: {
+//!         "#1": Atom(atom_type: Wall, velocity: None, durability: Some(2)),
+//!     },
+//! )
+//! map: (-1, 1) to (3, -1)
+//! . . . . .
+//! . B>. oB#1
+//! . . . . .
+//! ```
+//!
+//! The `map:` line gives the position of the top left cell. Cells are two characters wide and
+//! use the symbols in [`LEGEND`](super::board::LEGEND), so `B>` is a basic atom moving east and
+//! `oB` is a goal for a basic atom. Anything the symbols can't describe, such as emitters,
+//! switches or walls with durability, gets an entry in the header's legend, which takes
+//! precedence over the usual symbols. If there are no goals on the map, the header's `goal` is
+//! used instead.
+//!
+//! Text levels are loaded from `.level.txt` files, and can be converted to and from RON with
+//! `level_tool convert`.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, io::Reader},
+    prelude::*,
+};
+use ron::ser::PrettyConfig;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    atom::AtomType,
+    board::{
+        Board, CLOSED_GATE, DETECTOR, EMITTER, EMPTY, GOAL, OPEN_GATE, STILL, SWITCH,
+        atom_from_glyph, atom_glyph, direction_from_glyph, direction_glyph,
+    },
+    level::{
+        InvalidLevelError, Level, LevelAtom, LevelDetector, LevelEmitter, LevelGate, LevelGoal,
+        LevelGoalPosition, LevelHint, LevelPar, LevelPlacement, LevelSwitch,
+    },
+    movement::{CardinalDirection, Movement},
+    reaction::Decay,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset_loader::<TextLevelAssetLoader>();
+}
+
+/// The file extension of text levels
+pub const EXTENSION: &str = "level.txt";
+
+/// Starts the line that separates the header from the map
+const MAP_PREFIX: &str = "map:";
+
+/// Second characters for legend symbols, after a first character saying what kind of thing the
+/// symbol is for. `o` and `v` are left out as they look like goals and arrows.
+const SYMBOL_SUFFIXES: &str = "123456789abcdefghijklmnpqrstuwxyz";
+
+/// Whether a file holds a text level rather than RON
+pub fn is_text_level(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(&format!(".{EXTENSION}")))
+}
+
+/// Everything in a level that isn't drawn on the map
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct LevelHeader {
+    sidebar_text: String,
+    level_complete_text: String,
+    placeable_atoms: Vec<AtomType>,
+    /// The goal, if it isn't to reach the goals drawn on the map
+    #[serde(default, skip_serializing_if = "no_goal")]
+    goal: LevelGoal,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    allow_moving_placement: bool,
+    #[serde(default)]
+    par: LevelPar,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    hints: Vec<LevelHint>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    solutions: Vec<Vec<LevelPlacement>>,
+    /// Cells that can't be drawn with the usual symbols, by their two character symbol
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    legend: BTreeMap<String, MapCell>,
+}
+
+fn no_goal(goal: &LevelGoal) -> bool {
+    matches!(goal, LevelGoal::None)
+}
+
+/// Something in one cell of the map. The fields are those of the level's own types, without the
+/// position.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+enum MapCell {
+    Atom {
+        atom_type: AtomType,
+        velocity: Option<Movement>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        decay: Option<Decay>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        durability: Option<u32>,
+    },
+    Goal(AtomType),
+    Emitter {
+        atom_type: AtomType,
+        direction: CardinalDirection,
+        interval: u32,
+        count: Option<u32>,
+    },
+    Detector {
+        atom_type: AtomType,
+        direction: Option<CardinalDirection>,
+        target: Option<u32>,
+    },
+    Switch {
+        atom_type: Option<AtomType>,
+        gates: Vec<IVec2>,
+    },
+    Gate {
+        #[serde(default)]
+        open: bool,
+    },
+}
+
+impl MapCell {
+    /// The first character of legend symbols for this cell
+    fn symbol_prefix(&self) -> char {
+        match self {
+            MapCell::Atom { atom_type, .. } => drawable_glyph(*atom_type).unwrap_or('P'),
+            MapCell::Goal(_) => GOAL,
+            MapCell::Emitter { .. } => EMITTER,
+            MapCell::Detector { .. } => DETECTOR,
+            MapCell::Switch { .. } => SWITCH,
+            MapCell::Gate { open: true } => OPEN_GATE,
+            MapCell::Gate { open: false } => CLOSED_GATE,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum TextLevelError {
+    #[error("There's no `{MAP_PREFIX}` line after the header")]
+    NoMap,
+    #[error("Couldn't parse the header: {0}")]
+    Header(#[from] ron::error::SpannedError),
+    #[error("Line {line}: expected the position of the top left cell, like `{MAP_PREFIX} (0, 0)`")]
+    MapCorner { line: usize },
+    #[error("Legend symbols must be two characters and not blank, unlike `{0}`")]
+    LegendSymbol(String),
+    #[error("Line {line}, column {column}: `{cell}` isn't a symbol or in the legend")]
+    UnknownCell {
+        line: usize,
+        column: usize,
+        cell: String,
+    },
+    #[error("The map has goals, so the header can't have a goal as well")]
+    GoalConflict,
+    #[error("There's more than one thing at ({}, {}), so the level can't be drawn", .0.x, .0.y)]
+    Overlap(IVec2),
+    #[error("The level needs too many legend symbols starting with `{0}`")]
+    LegendFull(char),
+    #[error("Couldn't write the header: {0}")]
+    Ron(#[from] ron::Error),
+}
+
+/// The character drawn for an atom, if it can be read back as the same atom
+fn drawable_glyph(atom_type: AtomType) -> Option<char> {
+    let glyph = atom_glyph(atom_type);
+    (atom_from_glyph(glyph) == Some(atom_type)).then_some(glyph)
+}
+
+fn is_empty_cell(cell: [char; 2]) -> bool {
+    cell == [EMPTY, STILL] || cell == [' ', ' ']
+}
+
+/// What a cell means when it isn't in the legend
+fn default_cell([first, second]: [char; 2]) -> Option<MapCell> {
+    match (first, second) {
+        (CLOSED_GATE, STILL) => Some(MapCell::Gate { open: false }),
+        (OPEN_GATE, STILL) => Some(MapCell::Gate { open: true }),
+        (GOAL, glyph) => atom_from_glyph(glyph).map(MapCell::Goal),
+        (glyph, arrow) => {
+            let atom_type = atom_from_glyph(glyph)?;
+            let velocity = match arrow {
+                STILL => None,
+                arrow => Some(Movement::new(direction_from_glyph(arrow)?)),
+            };
+            Some(MapCell::Atom {
+                atom_type,
+                velocity,
+                decay: None,
+                durability: None,
+            })
+        }
+    }
+}
+
+/// How a cell is drawn without a legend entry, if it can be
+fn default_symbol(cell: &MapCell) -> Option<[char; 2]> {
+    match cell {
+        MapCell::Atom {
+            atom_type,
+            velocity,
+            decay: None,
+            durability: None,
+        } => {
+            let arrow = match velocity {
+                None => STILL,
+                Some(movement) if movement.speed == Movement::new(movement.direction).speed => {
+                    direction_glyph(movement.direction)
+                }
+                Some(_) => return None,
+            };
+            Some([drawable_glyph(*atom_type)?, arrow])
+        }
+        MapCell::Goal(atom_type) => Some([GOAL, drawable_glyph(*atom_type)?]),
+        MapCell::Gate { open } => Some([if *open { OPEN_GATE } else { CLOSED_GATE }, STILL]),
+        _ => None,
+    }
+}
+
+/// Read a text level
+pub fn level_from_text(text: &str) -> Result<Level, TextLevelError> {
+    let lines: Vec<&str> = text.lines().collect();
+    let map_line = lines
+        .iter()
+        .position(|line| line.trim_start().starts_with(MAP_PREFIX))
+        .ok_or(TextLevelError::NoMap)?;
+    let header: LevelHeader = ron::from_str(&lines[..map_line].join("\n"))?;
+    for symbol in header.legend.keys() {
+        let cell: Vec<char> = symbol.chars().collect();
+        if !matches!(cell[..], [first, second] if !is_empty_cell([first, second])) {
+            return Err(TextLevelError::LegendSymbol(symbol.clone()));
+        }
+    }
+
+    // Only the top left corner is needed, the bottom right is there for people reading the map
+    let corner = lines[map_line].trim_start()[MAP_PREFIX.len()..]
+        .split_once(')')
+        .and_then(|(corner, _)| ron::from_str::<IVec2>(&format!("{corner})")).ok())
+        .ok_or(TextLevelError::MapCorner { line: map_line + 1 })?;
+
+    let mut level = Level {
+        sidebar_text: header.sidebar_text,
+        level_complete_text: header.level_complete_text,
+        placeable_atoms: header.placeable_atoms,
+        allow_moving_placement: header.allow_moving_placement,
+        par: header.par,
+        hints: header.hints,
+        solutions: header.solutions,
+        ..default()
+    };
+    let mut goals = Vec::new();
+    for (row, line) in lines.iter().enumerate().skip(map_line + 1) {
+        let chars: Vec<char> = line.chars().collect();
+        for (column, cell) in chars.chunks(2).enumerate() {
+            // Lines may end half way through a cell, with its second character trimmed
+            let cell = [cell[0], cell.get(1).copied().unwrap_or(STILL)];
+            if is_empty_cell(cell) {
+                continue;
+            }
+            let symbol = String::from_iter(cell);
+            let map_cell = header
+                .legend
+                .get(&symbol)
+                .cloned()
+                .or_else(|| default_cell(cell))
+                .ok_or(TextLevelError::UnknownCell {
+                    line: row + 1,
+                    column: column * 2 + 1,
+                    cell: symbol,
+                })?;
+            let position = corner + IVec2::new(column as i32, -((row - map_line - 1) as i32));
+            add_cell(&mut level, &mut goals, position, map_cell);
+        }
+    }
+
+    level.goal = match (goals.is_empty(), header.goal) {
+        (true, goal) => goal,
+        (false, LevelGoal::None) => LevelGoal::ReachPositions(goals),
+        (false, _) => return Err(TextLevelError::GoalConflict),
+    };
+    Ok(level)
+}
+
+fn add_cell(level: &mut Level, goals: &mut Vec<LevelGoalPosition>, position: IVec2, cell: MapCell) {
+    match cell {
+        MapCell::Atom {
+            atom_type,
+            velocity,
+            decay,
+            durability,
+        } => level.atoms.push(LevelAtom {
+            atom_type,
+            position,
+            velocity,
+            decay,
+            durability,
+        }),
+        MapCell::Goal(atom_type) => goals.push(LevelGoalPosition {
+            atom_type,
+            position,
+        }),
+        MapCell::Emitter {
+            atom_type,
+            direction,
+            interval,
+            count,
+        } => level.emitters.push(LevelEmitter {
+            atom_type,
+            position,
+            direction,
+            interval,
+            count,
+        }),
+        MapCell::Detector {
+            atom_type,
+            direction,
+            target,
+        } => level.detectors.push(LevelDetector {
+            atom_type,
+            position,
+            direction,
+            target,
+        }),
+        MapCell::Switch { atom_type, gates } => level.switches.push(LevelSwitch {
+            position,
+            atom_type,
+            gates,
+        }),
+        MapCell::Gate { open } => level.gates.push(LevelGate { position, open }),
+    }
+}
+
+/// Everything in the level that is drawn on the map
+fn map_cells(level: &Level) -> Vec<(IVec2, MapCell)> {
+    let mut cells = Vec::new();
+    for atom in &level.atoms {
+        cells.push((
+            atom.position,
+            MapCell::Atom {
+                atom_type: atom.atom_type,
+                velocity: atom.velocity.clone(),
+                decay: atom.decay.clone(),
+                durability: atom.durability,
+            },
+        ));
+    }
+    if let LevelGoal::ReachPositions(goals) = &level.goal {
+        for goal in goals {
+            cells.push((goal.position, MapCell::Goal(goal.atom_type)));
+        }
+    }
+    for emitter in &level.emitters {
+        cells.push((
+            emitter.position,
+            MapCell::Emitter {
+                atom_type: emitter.atom_type,
+                direction: emitter.direction,
+                interval: emitter.interval,
+                count: emitter.count,
+            },
+        ));
+    }
+    for detector in &level.detectors {
+        cells.push((
+            detector.position,
+            MapCell::Detector {
+                atom_type: detector.atom_type,
+                direction: detector.direction,
+                target: detector.target,
+            },
+        ));
+    }
+    for switch in &level.switches {
+        cells.push((
+            switch.position,
+            MapCell::Switch {
+                atom_type: switch.atom_type,
+                gates: switch.gates.clone(),
+            },
+        ));
+    }
+    for gate in &level.gates {
+        cells.push((gate.position, MapCell::Gate { open: gate.open }));
+    }
+    cells
+}
+
+/// Write a level as a text level. Fails if two things in the level share a cell.
+pub fn level_to_text(level: &Level) -> Result<String, TextLevelError> {
+    let mut cells = map_cells(level);
+    // Legend symbols are numbered in reading order, so the same level is always written the same
+    cells.sort_by_key(|(position, _)| (-position.y, position.x));
+    if let Some(pair) = cells.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+        return Err(TextLevelError::Overlap(pair[0].0));
+    }
+
+    let mut board = Board::for_level(level, &[]);
+    let mut legend = BTreeMap::new();
+    // Legend symbols by the RON of their cell, so that identical cells share a symbol
+    let mut symbols: HashMap<String, [char; 2]> = HashMap::new();
+    for (position, cell) in cells {
+        let symbol = match default_symbol(&cell) {
+            Some(symbol) => symbol,
+            None => {
+                let key = ron::to_string(&cell)?;
+                match symbols.get(&key) {
+                    Some(&symbol) => symbol,
+                    None => {
+                        let symbol = new_symbol(&legend, cell.symbol_prefix())?;
+                        legend.insert(String::from_iter(symbol), cell);
+                        symbols.insert(key, symbol);
+                        symbol
+                    }
+                }
+            }
+        };
+        board.draw(position, symbol);
+    }
+
+    let goal = match &level.goal {
+        LevelGoal::ReachPositions(goals) if !goals.is_empty() => LevelGoal::None,
+        goal => goal.clone(),
+    };
+    let header = LevelHeader {
+        sidebar_text: level.sidebar_text.clone(),
+        level_complete_text: level.level_complete_text.clone(),
+        placeable_atoms: level.placeable_atoms.clone(),
+        goal,
+        allow_moving_placement: level.allow_moving_placement,
+        par: level.par.clone(),
+        hints: level.hints.clone(),
+        solutions: level.solutions.clone(),
+        legend,
+    };
+    // Deeper values, like legend entries and solutions, are kept to one line each
+    let header = ron::ser::to_string_pretty(&header, PrettyConfig::default().depth_limit(2))?;
+    Ok(format!("{header}\n{MAP_PREFIX} {board}"))
+}
+
+/// The first unused legend symbol starting with the given character
+fn new_symbol(
+    legend: &BTreeMap<String, MapCell>,
+    prefix: char,
+) -> Result<[char; 2], TextLevelError> {
+    SYMBOL_SUFFIXES
+        .chars()
+        .map(|suffix| [prefix, suffix])
+        .find(|&symbol| {
+            !legend.contains_key(&String::from_iter(symbol)) && default_cell(symbol).is_none()
+        })
+        .ok_or(TextLevelError::LegendFull(prefix))
+}
+
+#[derive(Default)]
+struct TextLevelAssetLoader;
+
+#[non_exhaustive]
+#[derive(Debug, Error)]
+enum TextLevelAssetLoaderError {
+    /// An [IO](std::io) Error
+    #[error("Could not load asset: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not read text: {0}")]
+    Utf8(#[from] std::string::FromUtf8Error),
+    #[error("Could not parse text level: {0}")]
+    TextLevel(#[from] TextLevelError),
+    #[error("Invalid level: {0}")]
+    Invalid(#[from] InvalidLevelError),
+}
+
+impl AssetLoader for TextLevelAssetLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = TextLevelAssetLoaderError;
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let level = level_from_text(&String::from_utf8(bytes)?)?;
+        level.check_portals()?;
+        Ok(level)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[EXTENSION]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LEVELS, game::level::read_level_file};
+
+    /// The level as RON, with everything drawn on the map sorted by position, since a text level
+    /// lists things in the order they're drawn
+    fn normalised_ron(mut level: Level) -> String {
+        let key = |position: IVec2| (position.x, position.y);
+        level.atoms.sort_by_key(|atom| key(atom.position));
+        level.emitters.sort_by_key(|emitter| key(emitter.position));
+        level
+            .detectors
+            .sort_by_key(|detector| key(detector.position));
+        level.switches.sort_by_key(|switch| key(switch.position));
+        level.gates.sort_by_key(|gate| key(gate.position));
+        if let LevelGoal::ReachPositions(goals) = &mut level.goal {
+            goals.sort_by_key(|goal| key(goal.position));
+        }
+        ron::to_string(&level).unwrap()
+    }
+
+    /// Write a level as text and read it back, checking that nothing changed
+    fn assert_round_trips(name: &str, level: Level) {
+        let text =
+            level_to_text(&level).unwrap_or_else(|error| panic!("Couldn't write {name}: {error}"));
+        let read = level_from_text(&text)
+            .unwrap_or_else(|error| panic!("Couldn't read {name} back: {error}\n{text}"));
+        assert_eq!(
+            normalised_ron(level),
+            normalised_ron(read.clone()),
+            "{name} changed:\n{text}"
+        );
+        assert_eq!(
+            level_to_text(&read).unwrap(),
+            text,
+            "{name} was written differently"
+        );
+    }
+
+    #[test]
+    fn campaign_levels_round_trip() {
+        for file in LEVELS {
+            let path = format!("{}/assets/levels/{file}", env!("CARGO_MANIFEST_DIR"));
+            let level = read_level_file(path.as_ref())
+                .unwrap_or_else(|error| panic!("Couldn't load {file}: {error}"));
+            assert_round_trips(file, level);
+        }
+    }
+
+    #[test]
+    fn long_portal_ids_round_trip() {
+        // Portal 12 would be drawn the same as portal 2 if it didn't go in the legend
+        let level = level_from_text(
+            r#"(
+    sidebar_text: "",
+    level_complete_text: "",
+    placeable_atoms: [Basic],
+    legend: {
+        "P1": Atom(atom_type: Portal(12), velocity: None),
+    },
+)
+map: (0, 1) to (3, 0)
+P1. 2 oB
+B>2 . P1
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            level
+                .atoms
+                .iter()
+                .filter(|atom| atom.atom_type == AtomType::Portal(12))
+                .count(),
+            2
+        );
+        assert_round_trips("Portal 12", level);
+    }
+}
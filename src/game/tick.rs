@@ -0,0 +1,66 @@
+//! A fixed-length clock that runs while an experiment is in progress.
+//!
+//! Board elements that act on a schedule (rather than on contact) count time in ticks.
+
+use bevy::prelude::*;
+
+use crate::{AppSystems, PausableSystems};
+
+use super::state::GameState;
+
+/// Length of a tick in seconds. An atom moving at the default speed travels exactly one grid
+/// cell per tick.
+pub const TICK_DURATION_SECS: f32 = 0.5;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ExperimentClock>();
+    app.add_systems(OnEnter(GameState::Running), reset_clock);
+    app.add_systems(
+        Update,
+        tick_clock
+            .run_if(in_state(GameState::Running))
+            .in_set(AppSystems::TickTimers)
+            .in_set(PausableSystems),
+    );
+}
+
+#[derive(Resource)]
+pub struct ExperimentClock {
+    timer: Timer,
+    tick: u32,
+}
+
+impl Default for ExperimentClock {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(TICK_DURATION_SECS, TimerMode::Repeating),
+            tick: 0,
+        }
+    }
+}
+
+impl ExperimentClock {
+    /// Number of ticks that have elapsed since the experiment was started.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Whether a new tick started this frame.
+    pub fn just_ticked(&self) -> bool {
+        self.timer.just_finished()
+    }
+
+    /// How far through the current tick the experiment is, from 0.0 to 1.0.
+    pub fn tick_fraction(&self) -> f32 {
+        self.timer.fraction()
+    }
+}
+
+fn reset_clock(mut clock: ResMut<ExperimentClock>) {
+    *clock = ExperimentClock::default();
+}
+
+fn tick_clock(time: Res<Time>, mut clock: ResMut<ExperimentClock>) {
+    clock.timer.tick(time.delta());
+    clock.tick += clock.timer.times_finished_this_tick();
+}
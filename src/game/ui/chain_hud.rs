@@ -0,0 +1,131 @@
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems,
+    game::{chain::ChainStats, collision::ReactionEvent},
+    screens::Screen,
+    theme::palette::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (
+            update_chain_hud.run_if(resource_changed::<ChainStats>),
+            celebrate_long_chains,
+            fade_chain_popups,
+        )
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Chains at least this deep get a pop-up
+const CELEBRATION_DEPTH: u32 = 4;
+
+/// How long the pop-up stays on screen
+const POPUP_DURATION_SECS: f32 = 2.0;
+
+#[derive(Component)]
+struct ChainHudText;
+
+#[derive(Component)]
+struct ChainPopup(Timer);
+
+pub(super) fn chain_hud() -> impl Bundle {
+    (
+        Name::new("Chain HUD"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(16.0),
+            right: Val::Px(16.0),
+            padding: UiRect::all(Val::Px(12.0)),
+            border: UiRect::all(Val::Px(2.0)),
+            ..Default::default()
+        },
+        BorderColor(ACCENT),
+        BackgroundColor(BACKGROUND.with_alpha(0.8)),
+        BorderRadius::all(Val::Px(8.0)),
+        Pickable::IGNORE,
+        children![(
+            Name::new("Chain HUD Text"),
+            ChainHudText,
+            Text::new(chain_hud_text(&ChainStats::default())),
+            TextFont::from_font_size(16.0),
+            TextColor(OFF_WHITE),
+            Pickable::IGNORE,
+        )],
+    )
+}
+
+fn chain_hud_text(stats: &ChainStats) -> String {
+    format!(
+        "Chain depth: {} (max {})\nReactions: {}",
+        stats.current_depth, stats.max_depth, stats.reactions
+    )
+}
+
+fn update_chain_hud(stats: Res<ChainStats>, mut texts: Query<&mut Text, With<ChainHudText>>) {
+    for mut text in &mut texts {
+        text.0 = chain_hud_text(&stats);
+    }
+}
+
+fn celebrate_long_chains(
+    mut commands: Commands,
+    mut reactions: EventReader<ReactionEvent>,
+    mut celebrated_depth: Local<u32>,
+    stats: Res<ChainStats>,
+) {
+    // A new experiment has started
+    if stats.max_depth < *celebrated_depth {
+        *celebrated_depth = 0;
+    }
+    for reaction in reactions.read() {
+        if reaction.depth >= CELEBRATION_DEPTH && reaction.depth > *celebrated_depth {
+            *celebrated_depth = reaction.depth;
+            commands.spawn((
+                Name::new("Chain Popup"),
+                ChainPopup(Timer::from_seconds(POPUP_DURATION_SECS, TimerMode::Once)),
+                Node {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(15.0),
+                    width: Val::Percent(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
+                Pickable::IGNORE,
+                StateScoped(Screen::Gameplay),
+                children![(
+                    Text::new(format!("Chain reaction x{}!", reaction.depth)),
+                    TextFont::from_font_size(48.0),
+                    TextColor(HEADER_TEXT),
+                    Pickable::IGNORE,
+                )],
+            ));
+        }
+    }
+}
+
+/// Popups drift upwards and fade out before being removed
+fn fade_chain_popups(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut popups: Query<(Entity, &mut ChainPopup, &mut Node, &Children)>,
+    mut texts: Query<&mut TextColor>,
+) {
+    for (entity, mut popup, mut node, children) in &mut popups {
+        popup.0.tick(time.delta());
+        if popup.0.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let progress = popup.0.fraction();
+        node.top = Val::Percent(15.0 - 5.0 * progress);
+        for &child in children {
+            if let Ok(mut color) = texts.get_mut(child) {
+                color.0 = HEADER_TEXT.with_alpha(1.0 - progress * progress);
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems,
+    game::{
+        hint::{RevealHint, RevealedHints},
+        level::{CurrentLevel, Level},
+    },
+    screens::Screen,
+    theme::{palette::*, widget},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        update_hint_list
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Component)]
+struct HintList;
+
+/// Revealed hints and a button to reveal the next one. Hidden for levels without hints.
+pub(super) fn hint_panel(hint_count: usize) -> impl Bundle {
+    (
+        Name::new("Hints"),
+        Node {
+            display: if hint_count == 0 {
+                Display::None
+            } else {
+                Display::Flex
+            },
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(8.0),
+            ..Default::default()
+        },
+        children![
+            (
+                Name::new("Hint List"),
+                HintList,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    ..Default::default()
+                },
+            ),
+            widget::sidebar_button("Reveal hint", reveal_hint),
+        ],
+    )
+}
+
+fn reveal_hint(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(RevealHint);
+}
+
+fn update_hint_list(
+    mut commands: Commands,
+    revealed: Res<RevealedHints>,
+    current_level: Res<CurrentLevel>,
+    level_assets: Res<Assets<Level>>,
+    lists: Query<(Entity, Ref<HintList>)>,
+) {
+    let Ok(level) = current_level.get_level(&level_assets) else {
+        return;
+    };
+    for (entity, list) in &lists {
+        // The list is respawned along with the rest of the sidebar when the level changes
+        if !revealed.is_changed() && !list.is_added() {
+            continue;
+        }
+        commands
+            .entity(entity)
+            .despawn_related::<Children>()
+            .with_children(|parent| {
+                parent.spawn((
+                    Name::new("Hint Count"),
+                    Text::new(format!(
+                        "Hints: {}/{}",
+                        revealed.0.min(level.hints.len()),
+                        level.hints.len()
+                    )),
+                    TextFont::from_font_size(16.0),
+                    TextColor(OFF_WHITE),
+                ));
+                for (number, hint) in level.hints.iter().take(revealed.0).enumerate() {
+                    parent.spawn((
+                        Name::new("Hint"),
+                        Text::new(format!("{}. {}", number + 1, hint.text)),
+                        TextFont::from_font_size(14.0),
+                        TextColor(LABEL_TEXT),
+                    ));
+                }
+            });
+    }
+}
@@ -2,12 +2,27 @@ use bevy::prelude::*;
 
 use crate::{screens::Screen, theme::widget::ui_root};
 
-use super::level::{CurrentLevel, Level};
+use super::{
+    daily::DailyChallenge,
+    level::{CurrentLevel, Level},
+};
 
+mod chain_hud;
+mod hint_list;
+mod reaction_log;
 mod sidebar;
+mod solver_panel;
+mod toast;
 mod tray;
 
 pub(super) fn plugin(app: &mut App) {
+    app.add_plugins((
+        chain_hud::plugin,
+        hint_list::plugin,
+        reaction_log::plugin,
+        solver_panel::plugin,
+        toast::plugin,
+    ));
     app.add_systems(
         Update,
         (init_level_ui, tray::update_drag_icons)
@@ -25,18 +40,28 @@ fn init_level_ui(
     mut commands: Commands,
     current_level: Res<CurrentLevel>,
     level_assets: Res<Assets<Level>>,
+    reaction_log_visible: Res<reaction_log::ReactionLogVisible>,
+    daily: Option<Res<DailyChallenge>>,
 ) {
     // despawn previous level UI if present
     for entity in prev_root {
         commands.entity(entity).despawn();
     }
+    // The solver would give away the answers to campaign levels and the daily challenge
+    let show_solver = match &*current_level {
+        CurrentLevel::Editing(_) => true,
+        CurrentLevel::Custom(handle) => daily.is_none_or(|daily| daily.level != *handle),
+        _ => false,
+    };
     // re-draw UI for current level
     commands.spawn((
         ui_root("UI root"),
         StateScoped(Screen::Gameplay),
         UiRoot,
         children![
-            sidebar::sidebar(&current_level, &level_assets),
+            sidebar::sidebar(&current_level, &level_assets, show_solver),
+            reaction_log::reaction_log_panel(reaction_log_visible.0),
+            chain_hud::chain_hud(),
             tray::tray()
         ],
     ));
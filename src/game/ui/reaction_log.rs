@@ -0,0 +1,231 @@
+use bevy::{
+    input::{common_conditions::input_just_pressed, mouse::MouseScrollUnit},
+    prelude::*,
+};
+
+use crate::{
+    AppSystems,
+    game::{collision::ReactionEvent, state::GameState},
+    screens::Screen,
+    theme::palette::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ReactionLog>();
+    app.init_resource::<ReactionLogVisible>();
+    app.add_systems(OnEnter(GameState::Running), clear_reaction_log);
+    app.add_observer(scroll_reaction_log);
+    app.add_systems(
+        Update,
+        toggle_reaction_log
+            .in_set(AppSystems::RecordInput)
+            .run_if(in_state(Screen::Gameplay).and(input_just_pressed(KeyCode::KeyL))),
+    );
+    app.add_systems(
+        Update,
+        (
+            record_reactions,
+            update_reaction_log_entries.run_if(resource_changed::<ReactionLog>),
+            update_reaction_log_visibility.run_if(resource_changed::<ReactionLogVisible>),
+            draw_highlighted_cell,
+        )
+            .chain()
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// Every reaction that has happened during the current experiment, in order
+#[derive(Resource, Default)]
+pub struct ReactionLog(pub Vec<ReactionEvent>);
+
+#[derive(Resource, Default)]
+pub struct ReactionLogVisible(pub bool);
+
+/// A grid cell picked from the reaction log, highlighted for a short time
+#[derive(Resource)]
+struct HighlightedCell {
+    position: IVec2,
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct ReactionLogPanel;
+
+#[derive(Component)]
+struct ReactionLogEntries;
+
+/// Grid position of the reaction an entry describes
+#[derive(Component)]
+struct ReactionLogEntry(IVec2);
+
+/// How long a cell stays highlighted after clicking on a log entry
+const HIGHLIGHT_DURATION_SECS: f32 = 3.0;
+
+/// Pixels scrolled per line when using a mouse wheel
+const SCROLL_LINE_HEIGHT: f32 = 20.0;
+
+pub(super) fn reaction_log_panel(visible: bool) -> impl Bundle {
+    (
+        Name::new("Reaction Log"),
+        ReactionLogPanel,
+        Node {
+            display: if visible {
+                Display::Flex
+            } else {
+                Display::None
+            },
+            position_type: PositionType::Absolute,
+            top: Val::Px(0.0),
+            left: Val::Px(320.0),
+            width: Val::Px(260.0),
+            height: Val::Percent(100.0),
+            padding: UiRect::all(Val::Px(16.0)),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(8.0),
+            border: UiRect::right(Val::Px(2.0)),
+            ..Default::default()
+        },
+        BorderColor(ACCENT),
+        BackgroundColor(BACKGROUND.with_alpha(0.9)),
+        children![
+            (
+                Name::new("Reaction Log Header"),
+                Text::new("Reaction log"),
+                TextFont::from_font_size(20.0),
+                TextColor(OFF_WHITE),
+            ),
+            (
+                Name::new("Reaction Log Hint"),
+                Text::new("Click a reaction to highlight where it happened. <L> to hide."),
+                TextFont::from_font_size(12.0),
+                TextColor(LABEL_TEXT),
+            ),
+            (
+                Name::new("Reaction Log Entries"),
+                ReactionLogEntries,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    flex_grow: 1.0,
+                    row_gap: Val::Px(4.0),
+                    overflow: Overflow::scroll_y(),
+                    ..Default::default()
+                },
+                ScrollPosition::default(),
+            )
+        ],
+    )
+}
+
+fn clear_reaction_log(mut log: ResMut<ReactionLog>, mut commands: Commands) {
+    log.0.clear();
+    commands.remove_resource::<HighlightedCell>();
+}
+
+fn toggle_reaction_log(mut visible: ResMut<ReactionLogVisible>) {
+    visible.0 = !visible.0;
+}
+
+fn record_reactions(mut reactions: EventReader<ReactionEvent>, mut log: ResMut<ReactionLog>) {
+    if !reactions.is_empty() {
+        log.0.extend(reactions.read().cloned());
+    }
+}
+
+fn update_reaction_log_visibility(
+    visible: Res<ReactionLogVisible>,
+    mut panels: Query<&mut Node, With<ReactionLogPanel>>,
+) {
+    for mut node in &mut panels {
+        node.display = if visible.0 {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+}
+
+fn update_reaction_log_entries(
+    mut commands: Commands,
+    log: Res<ReactionLog>,
+    containers: Query<Entity, With<ReactionLogEntries>>,
+) {
+    for container in &containers {
+        commands
+            .entity(container)
+            .despawn_related::<Children>()
+            .with_children(|parent| {
+                if log.0.is_empty() {
+                    parent.spawn((
+                        Name::new("Reaction Log Empty"),
+                        Text::new("No reactions yet"),
+                        TextFont::from_font_size(14.0),
+                        TextColor(LABEL_TEXT),
+                    ));
+                }
+                for reaction in &log.0 {
+                    parent
+                        .spawn((
+                            Name::new("Reaction Log Entry"),
+                            ReactionLogEntry(reaction.position),
+                            Button,
+                            Text::new(reaction.describe()),
+                            TextFont::from_font_size(14.0),
+                            TextColor(OFF_WHITE),
+                        ))
+                        .observe(highlight_reaction_cell);
+                }
+            });
+    }
+}
+
+fn highlight_reaction_cell(
+    trigger: Trigger<Pointer<Click>>,
+    entries: Query<&ReactionLogEntry>,
+    mut commands: Commands,
+) {
+    if let Ok(entry) = entries.get(trigger.target()) {
+        commands.insert_resource(HighlightedCell {
+            position: entry.0,
+            timer: Timer::from_seconds(HIGHLIGHT_DURATION_SECS, TimerMode::Once),
+        });
+    }
+}
+
+fn scroll_reaction_log(
+    trigger: Trigger<Pointer<Scroll>>,
+    mut scroll_positions: Query<&mut ScrollPosition, With<ReactionLogEntries>>,
+) {
+    let Ok(mut scroll_position) = scroll_positions.get_mut(trigger.target()) else {
+        return;
+    };
+    let scroll = trigger.event();
+    let dy = match scroll.unit {
+        MouseScrollUnit::Line => scroll.y * SCROLL_LINE_HEIGHT,
+        MouseScrollUnit::Pixel => scroll.y,
+    };
+    scroll_position.offset_y = (scroll_position.offset_y - dy).max(0.0);
+}
+
+fn draw_highlighted_cell(
+    mut commands: Commands,
+    time: Res<Time>,
+    highlighted: Option<ResMut<HighlightedCell>>,
+    mut gizmos: Gizmos,
+) {
+    let Some(mut highlighted) = highlighted else {
+        return;
+    };
+    highlighted.timer.tick(time.delta());
+    if highlighted.timer.finished() {
+        commands.remove_resource::<HighlightedCell>();
+        return;
+    }
+    // Pulse the highlight so that it stands out against the grid
+    let pulse = 0.5 + 0.5 * (highlighted.timer.elapsed_secs() * 8.0).sin();
+    gizmos.rect_2d(
+        Isometry2d::from_translation(highlighted.position.as_vec2()),
+        Vec2::splat(0.7 + 0.1 * pulse),
+        LinearRgba::rgb(1.0, 0.9, 0.3).with_alpha(0.5 + 0.5 * pulse),
+    );
+}
@@ -3,32 +3,51 @@ use std::time::Duration;
 use bevy::prelude::*;
 use bevy_easings::Ease;
 
+use super::{hint_list::hint_panel, reaction_log::ReactionLogVisible, solver_panel::solver_panel};
 use crate::{
     game::{
         level::{CurrentLevel, Level, PlacedLevelAtoms},
+        replay::SaveReplay,
+        share::CopyShareCode,
+        solution::ShowSolution,
         state::GameState,
     },
     screens::Screen,
     theme::{palette::*, widget},
 };
 
-pub(super) fn sidebar(current_level: &CurrentLevel, level_assets: &Assets<Level>) -> impl Bundle {
+pub(super) fn sidebar(
+    current_level: &CurrentLevel,
+    level_assets: &Assets<Level>,
+    show_solver: bool,
+) -> impl Bundle {
     let text = if let Ok(level) = current_level.get_level(level_assets) {
         format!(
-            "{}\n\nControls:\n<esc>: pause\n<spacebar>: start/stop\n Left click and drag an atom from the tray to place it.\nRight click to remove a placed atom.",
-            level.sidebar_text
+            "{}\n\nControls:\n<esc>: pause\n<spacebar>: start/stop\n<L>: show/hide reaction log\n Left click and drag an atom from the tray to place it.\nRight click to remove a placed atom.{}",
+            level.sidebar_text,
+            if level.allow_moving_placement {
+                "\nScroll or use the arrow keys while dragging an atom to choose its launch direction."
+            } else {
+                ""
+            }
         )
     } else {
         "Sandbox".to_string()
     };
-    let heading = if let CurrentLevel::Loaded {
-        level_handle: _,
-        level_index,
-    } = *current_level
-    {
-        format!("Level {}", level_index + 1)
-    } else {
-        "Sandbox".to_string()
+    let hint_count = current_level
+        .get_level(level_assets)
+        .map_or(0, |level| level.hints.len());
+    let has_solution = current_level.get_index().is_some()
+        && current_level
+            .get_level(level_assets)
+            .is_ok_and(|level| !level.solutions.is_empty());
+    let heading = match *current_level {
+        CurrentLevel::Loaded {
+            level_handle: _,
+            level_index,
+        } => format!("Level {}", level_index + 1),
+        CurrentLevel::Custom(_) => "Custom Level".to_string(),
+        _ => "Sandbox".to_string(),
     };
     (
         Name::new("Sidebar"),
@@ -102,8 +121,25 @@ pub(super) fn sidebar(current_level: &CurrentLevel, level_assets: &Assets<Level>
                     ..Default::default()
                 },
             ),
+            hint_panel(hint_count),
+            solver_panel(show_solver),
+            (
+                Name::new("Solution"),
+                Node {
+                    display: if has_solution {
+                        Display::Flex
+                    } else {
+                        Display::None
+                    },
+                    ..Default::default()
+                },
+                children![widget::sidebar_button("Show solution", show_solution)],
+            ),
             widget::sidebar_button("Start / Stop experiment", start_stop),
             widget::sidebar_button("Reset level", reset),
+            widget::sidebar_button("Reaction log", toggle_reaction_log),
+            widget::sidebar_button("Save replay", save_replay),
+            widget::sidebar_button("Copy level code", copy_level_code),
             widget::sidebar_button("Quit to title", quit_to_title),
         ],
     )
@@ -130,6 +166,24 @@ fn reset(
     next_state.set(GameState::RestartLevel);
 }
 
+fn show_solution(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(ShowSolution);
+}
+
+fn toggle_reaction_log(_: Trigger<Pointer<Click>>, mut visible: ResMut<ReactionLogVisible>) {
+    visible.0 = !visible.0;
+}
+
+fn save_replay(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(SaveReplay);
+}
+
+fn copy_level_code(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(CopyShareCode {
+        with_solution: false,
+    });
+}
+
 fn quit_to_title(_: Trigger<Pointer<Click>>, mut next_screen: ResMut<NextState<Screen>>) {
     next_screen.set(Screen::Title);
 }
@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+use crate::{
+    AppSystems,
+    game::solver::{CheckSolvable, FindAllSolutions, SolverStatus},
+    screens::Screen,
+    theme::{palette::*, widget},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        update_solver_status_text
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+#[derive(Component)]
+struct SolverStatusText;
+
+/// Buttons to check whether the level can be completed and to list all of its solutions, along
+/// with the result. Only shown for levels made outside the campaign.
+pub(super) fn solver_panel(visible: bool) -> impl Bundle {
+    (
+        Name::new("Solver"),
+        Node {
+            display: if visible {
+                Display::Flex
+            } else {
+                Display::None
+            },
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(8.0),
+            ..Default::default()
+        },
+        children![
+            widget::sidebar_button("Check solvable", check_solvable),
+            widget::sidebar_button("Find all solutions", find_all_solutions),
+            (
+                Name::new("Solver Status"),
+                SolverStatusText,
+                Text::default(),
+                TextFont::from_font_size(14.0),
+                TextColor(OFF_WHITE),
+            ),
+        ],
+    )
+}
+
+fn check_solvable(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(CheckSolvable);
+}
+
+fn find_all_solutions(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(FindAllSolutions);
+}
+
+fn update_solver_status_text(
+    status: Res<SolverStatus>,
+    mut texts: Query<(&mut Text, Ref<SolverStatusText>)>,
+) {
+    for (mut text, marker) in &mut texts {
+        // The text is respawned along with the rest of the sidebar when the level changes
+        if !status.is_changed() && !marker.is_added() {
+            continue;
+        }
+        text.0 = match &*status {
+            SolverStatus::Idle => String::new(),
+            SolverStatus::Running => "Searching...".to_string(),
+            SolverStatus::Finished(description) => description.clone(),
+        };
+    }
+}
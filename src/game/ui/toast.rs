@@ -0,0 +1,154 @@
+use bevy::{prelude::*, text::LineBreak};
+
+use crate::{
+    AppSystems,
+    game::{achievement::AchievementUnlocked, share::ShareCodeNotCopied},
+    screens::Screen,
+    theme::palette::*,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Screen::Gameplay), spawn_toast_container);
+    app.add_systems(
+        Update,
+        (show_achievement_toasts, show_share_code_toasts, fade_toasts)
+            .in_set(AppSystems::Update)
+            .run_if(in_state(Screen::Gameplay)),
+    );
+}
+
+/// How long a toast stays on screen
+const TOAST_DURATION_SECS: f32 = 4.0;
+
+/// Share codes have to be copied out by hand, so they stay on screen for longer
+const SHARE_CODE_TOAST_DURATION_SECS: f32 = 30.0;
+
+/// Toasts fade out over this final part of their lifetime
+const TOAST_FADE_SECS: f32 = 1.0;
+
+#[derive(Component)]
+struct ToastContainer;
+
+#[derive(Component)]
+struct Toast(Timer);
+
+/// Toasts live outside the level UI so they survive moving on to the next level
+fn spawn_toast_container(mut commands: Commands) {
+    commands.spawn((
+        Name::new("Toasts"),
+        ToastContainer,
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(16.0),
+            right: Val::Px(16.0),
+            flex_direction: FlexDirection::ColumnReverse,
+            row_gap: Val::Px(8.0),
+            ..Default::default()
+        },
+        GlobalZIndex(3),
+        Pickable::IGNORE,
+        StateScoped(Screen::Gameplay),
+    ));
+}
+
+/// A toast with a heading and a line of detail underneath
+fn toast(
+    name: &'static str,
+    heading: String,
+    detail: impl Bundle,
+    duration_secs: f32,
+) -> impl Bundle {
+    (
+        Name::new(name),
+        Toast(Timer::from_seconds(duration_secs, TimerMode::Once)),
+        Node {
+            flex_direction: FlexDirection::Column,
+            max_width: Val::Px(480.0),
+            padding: UiRect::all(Val::Px(12.0)),
+            border: UiRect::all(Val::Px(2.0)),
+            row_gap: Val::Px(4.0),
+            ..Default::default()
+        },
+        BorderColor(ACCENT),
+        BackgroundColor(BACKGROUND.with_alpha(0.9)),
+        BorderRadius::all(Val::Px(8.0)),
+        Pickable::IGNORE,
+        children![
+            (
+                Text::new(heading),
+                TextFont::from_font_size(18.0),
+                TextColor(HEADER_TEXT),
+                Pickable::IGNORE,
+            ),
+            (
+                detail,
+                TextFont::from_font_size(14.0),
+                TextColor(LABEL_TEXT),
+                Pickable::IGNORE,
+            ),
+        ],
+    )
+}
+
+fn show_achievement_toasts(
+    mut commands: Commands,
+    mut unlocked: EventReader<AchievementUnlocked>,
+    container: Single<Entity, With<ToastContainer>>,
+) {
+    for AchievementUnlocked(achievement) in unlocked.read() {
+        commands.entity(*container).with_child(toast(
+            "Achievement Toast",
+            format!("Achievement unlocked: {}", achievement.name),
+            Text::new(achievement.description),
+            TOAST_DURATION_SECS,
+        ));
+    }
+}
+
+fn show_share_code_toasts(
+    mut commands: Commands,
+    mut not_copied: EventReader<ShareCodeNotCopied>,
+    container: Single<Entity, With<ToastContainer>>,
+) {
+    for ShareCodeNotCopied(text) in not_copied.read() {
+        commands.entity(*container).with_child(toast(
+            "Share Code Toast",
+            "Couldn't copy to the clipboard. Here's the code:".to_string(),
+            (
+                Text::new(text.clone()),
+                // Codes have no spaces to wrap at
+                TextLayout::new_with_linebreak(LineBreak::AnyCharacter),
+            ),
+            SHARE_CODE_TOAST_DURATION_SECS,
+        ));
+    }
+}
+
+fn fade_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(
+        Entity,
+        &mut Toast,
+        &mut BackgroundColor,
+        &mut BorderColor,
+        &Children,
+    )>,
+    mut texts: Query<&mut TextColor>,
+) {
+    for (entity, mut toast, mut background, mut border, children) in &mut toasts {
+        toast.0.tick(time.delta());
+        if toast.0.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let alpha = (toast.0.remaining_secs() / TOAST_FADE_SECS).min(1.0);
+        background.0 = BACKGROUND.with_alpha(0.9 * alpha);
+        border.0 = ACCENT.with_alpha(alpha);
+        for &child in children {
+            if let Ok(mut color) = texts.get_mut(child) {
+                color.0.set_alpha(alpha);
+            }
+        }
+    }
+}
@@ -55,12 +55,13 @@ fn drag_icon(atom_type: AtomType, atom_assets: &AtomAssets) -> impl Bundle {
                     ImageNode {
                         image: atom_type.get_image_handle(&atom_assets),
                         texture_atlas: match atom_type {
-                            AtomType::Wall => None,
+                            AtomType::Wall | AtomType::Portal(_) => None,
                             _ => Some(TextureAtlas {
                                 layout: atom_assets.atlas_layout.clone(),
                                 index: 8,
                             }),
                         },
+                        color: atom_type.get_color(),
                         ..Default::default()
                     },
                     BorderColor(OFF_WHITE),
@@ -0,0 +1,321 @@
+//! Checks a level for mistakes that can't be caught when it's loaded, such as two things on the
+//! same cell, switches linked to gates that don't exist, or reference solutions that don't work.
+
+use std::fmt;
+
+use bevy::{
+    platform::collections::{HashMap, HashSet},
+    prelude::*,
+};
+
+use super::{
+    level::{Level, LevelGoal, LevelPlacement},
+    simulation::simulate,
+    solver::level_positions,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Something that may be intended, but probably isn't
+    Warning,
+    /// Something that stops the level working as intended
+    Error,
+}
+
+/// A problem found in a level
+#[derive(Debug, Clone)]
+pub struct LevelIssue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl LevelIssue {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for LevelIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}: {}", self.message)
+    }
+}
+
+/// Check a level for mistakes. Reference solutions are simulated, so this can take a moment for
+/// levels with many of them.
+pub fn validate_level(level: &Level) -> Vec<LevelIssue> {
+    let mut issues = Vec::new();
+    check_overlaps(level, &mut issues);
+    check_goal(level, &mut issues);
+    check_tray(level, &mut issues);
+    check_portals(level, &mut issues);
+    check_switches(level, &mut issues);
+    for (index, hint) in level.hints.iter().enumerate() {
+        check_placements(
+            level,
+            &hint.atoms,
+            &format!("Hint {}", index + 1),
+            &mut issues,
+        );
+    }
+    check_solutions(level, &mut issues);
+    issues.sort_by_key(|issue| std::cmp::Reverse(issue.severity));
+    issues
+}
+
+fn describe_position(position: IVec2) -> String {
+    format!("({}, {})", position.x, position.y)
+}
+
+fn check_overlaps(level: &Level, issues: &mut Vec<LevelIssue>) {
+    let mut things: HashMap<IVec2, Vec<String>> = HashMap::new();
+    let mut add = |position: IVec2, thing: String| things.entry(position).or_default().push(thing);
+    for atom in &level.atoms {
+        add(atom.position, format!("a {:?} atom", atom.atom_type));
+    }
+    if let LevelGoal::ReachPositions(goals) = &level.goal {
+        for goal in goals {
+            add(goal.position, format!("a {:?} goal", goal.atom_type));
+        }
+    }
+    for emitter in &level.emitters {
+        add(emitter.position, "an emitter".to_string());
+    }
+    for detector in &level.detectors {
+        add(detector.position, "a detector".to_string());
+    }
+    for switch in &level.switches {
+        add(switch.position, "a switch".to_string());
+    }
+    for gate in &level.gates {
+        add(gate.position, "a gate".to_string());
+    }
+    let mut overlaps: Vec<_> = things
+        .into_iter()
+        .filter(|(_, things)| things.len() > 1)
+        .collect();
+    overlaps.sort_by_key(|(position, _)| (position.x, position.y));
+    for (position, things) in overlaps {
+        issues.push(LevelIssue::error(format!(
+            "{} is occupied by {}",
+            describe_position(position),
+            things.join(" and ")
+        )));
+    }
+}
+
+fn check_goal(level: &Level, issues: &mut Vec<LevelIssue>) {
+    match &level.goal {
+        LevelGoal::None => issues.push(LevelIssue::warning(
+            "The level has no goal, so it can never be completed",
+        )),
+        LevelGoal::ReachPositions(goals) if goals.is_empty() => issues.push(LevelIssue::error(
+            "The goal has no positions to reach, so it is complete straight away",
+        )),
+        LevelGoal::ReachPositions(_) => {}
+        LevelGoal::CreateAtom(atom_type) => {
+            if level.atoms.iter().any(|atom| atom.atom_type == *atom_type) {
+                issues.push(LevelIssue::error(format!(
+                    "The level already contains a {atom_type:?} atom, so the goal is complete \
+                     straight away"
+                )));
+            }
+            if level.placeable_atoms.contains(atom_type) {
+                issues.push(LevelIssue::warning(format!(
+                    "{atom_type:?} atoms can be placed, so placing one completes the goal"
+                )));
+            }
+        }
+        // Placed atoms start a chain at depth 0
+        LevelGoal::CreateAtomAtDepth {
+            atom_type,
+            depth: 0,
+        } if level.placeable_atoms.contains(atom_type) => {
+            issues.push(LevelIssue::warning(format!(
+                "{atom_type:?} atoms can be placed, so placing one completes the goal"
+            )));
+        }
+        LevelGoal::CreateAtomAtDepth { .. } => {}
+        LevelGoal::FillDetectors => {
+            if !level
+                .detectors
+                .iter()
+                .any(|detector| detector.target.is_some())
+            {
+                issues.push(LevelIssue::error(
+                    "The goal is to fill detectors, but no detector has a target",
+                ));
+            }
+        }
+    }
+    for detector in &level.detectors {
+        if detector.target == Some(0) {
+            issues.push(LevelIssue::warning(format!(
+                "The detector at {} has a target of 0, so is always full",
+                describe_position(detector.position)
+            )));
+        }
+    }
+    for emitter in &level.emitters {
+        if emitter.interval == 0 || emitter.count == Some(0) {
+            issues.push(LevelIssue::warning(format!(
+                "The emitter at {} never emits anything",
+                describe_position(emitter.position)
+            )));
+        }
+    }
+}
+
+fn check_tray(level: &Level, issues: &mut Vec<LevelIssue>) {
+    if level.placeable_atoms.is_empty() {
+        issues.push(LevelIssue::warning("There are no atoms to place"));
+    }
+    let mut seen = HashSet::new();
+    for atom_type in &level.placeable_atoms {
+        if !seen.insert(atom_type) {
+            issues.push(LevelIssue::warning(format!(
+                "{atom_type:?} appears in the tray more than once"
+            )));
+        }
+    }
+}
+
+fn check_portals(level: &Level, issues: &mut Vec<LevelIssue>) {
+    if let Err(error) = level.check_portals() {
+        issues.push(LevelIssue::error(error.to_string()));
+    }
+}
+
+fn check_switches(level: &Level, issues: &mut Vec<LevelIssue>) {
+    let gates: HashSet<IVec2> = level.gates.iter().map(|gate| gate.position).collect();
+    let mut linked = HashSet::new();
+    for switch in &level.switches {
+        for &gate in &switch.gates {
+            linked.insert(gate);
+            if !gates.contains(&gate) {
+                issues.push(LevelIssue::error(format!(
+                    "The switch at {} is linked to {}, where there's no gate",
+                    describe_position(switch.position),
+                    describe_position(gate)
+                )));
+            }
+        }
+    }
+    for gate in &level.gates {
+        if !linked.contains(&gate.position) {
+            issues.push(LevelIssue::warning(format!(
+                "The gate at {} isn't linked to a switch, so is always {}",
+                describe_position(gate.position),
+                if gate.open { "open" } else { "closed" }
+            )));
+        }
+    }
+}
+
+/// Check that the player could have placed the given atoms
+fn check_placements(
+    level: &Level,
+    placements: &[LevelPlacement],
+    name: &str,
+    issues: &mut Vec<LevelIssue>,
+) {
+    let occupied: HashSet<IVec2> = level_positions(level).into_iter().collect();
+    let mut placed = HashSet::new();
+    for placement in placements {
+        let position = describe_position(placement.position);
+        if occupied.contains(&placement.position) {
+            issues.push(LevelIssue::error(format!(
+                "{name} places an atom at {position}, which is already occupied"
+            )));
+        }
+        if !placed.insert(placement.position) {
+            issues.push(LevelIssue::error(format!(
+                "{name} places more than one atom at {position}"
+            )));
+        }
+        if !level.placeable_atoms.contains(&placement.atom_type) {
+            issues.push(LevelIssue::error(format!(
+                "{name} places a {:?} atom, which isn't in the tray",
+                placement.atom_type
+            )));
+        }
+        if placement.direction.is_some() && !level.allow_moving_placement {
+            issues.push(LevelIssue::error(format!(
+                "{name} launches the atom at {position}, but the level doesn't allow moving \
+                 placement"
+            )));
+        }
+    }
+}
+
+fn check_solutions(level: &Level, issues: &mut Vec<LevelIssue>) {
+    if level.solutions.is_empty() {
+        if !matches!(level.goal, LevelGoal::None) {
+            issues.push(LevelIssue::warning(
+                "The level has no reference solution, so it may not be possible",
+            ));
+        }
+        return;
+    }
+    let mut meets_par = false;
+    for (index, solution) in level.solutions.iter().enumerate() {
+        let name = format!("Solution {}", index + 1);
+        check_placements(level, solution, &name, issues);
+        let report = simulate(level, &LevelPlacement::layout(solution));
+        if !report.is_complete() {
+            issues.push(LevelIssue::error(format!(
+                "{name} doesn't complete the level ({:?} after {} ticks)",
+                report.outcome, report.ticks
+            )));
+            continue;
+        }
+        let par = &level.par;
+        meets_par |= par
+            .atoms
+            .is_none_or(|atoms| solution.len() <= atoms as usize)
+            && par.ticks.is_none_or(|ticks| report.ticks <= ticks)
+            && par
+                .reactions
+                .is_none_or(|reactions| report.reactions.len() <= reactions as usize);
+    }
+    if !meets_par {
+        issues.push(LevelIssue::warning(
+            "No reference solution meets every par target, so three stars may be impossible",
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LEVELS, game::level::read_level_file};
+
+    #[test]
+    fn campaign_levels_have_no_errors() {
+        for file in LEVELS {
+            let path = format!("{}/assets/levels/{file}", env!("CARGO_MANIFEST_DIR"));
+            let level = read_level_file(path.as_ref())
+                .unwrap_or_else(|error| panic!("Couldn't load {file}: {error}"));
+            let errors: Vec<_> = validate_level(&level)
+                .into_iter()
+                .filter(|issue| issue.severity == Severity::Error)
+                .map(|issue| issue.message)
+                .collect();
+            assert!(errors.is_empty(), "{file} has errors: {errors:?}");
+        }
+    }
+}
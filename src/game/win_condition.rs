@@ -11,17 +11,21 @@ use crate::{
 
 use super::{
     atom::{AtomAssets, AtomType},
+    chain::Lineage,
+    detector::Detector,
     level::{CurrentLevel, Level},
     placement::GridPos,
-    state::GameState,
+    state::{ExperimentSystems, GameState},
 };
 
 pub(super) fn plugin(app: &mut App) {
     app.add_systems(
         Update,
         (check_goal_collisions, check_win_condition)
+            .chain()
             .run_if(in_state(GameState::Running))
             .in_set(AppSystems::Update)
+            .in_set(ExperimentSystems::Goal)
             .in_set(PausableSystems),
     );
 }
@@ -91,15 +95,15 @@ fn check_goal_collisions(
 
 fn check_win_condition(
     goals: Query<&Goal>,
-    atoms: Query<&AtomType, Without<Goal>>,
+    atoms: Query<(&AtomType, Option<&Lineage>), Without<Goal>>,
+    detectors: Query<&Detector>,
     mut next_state: ResMut<NextState<GameState>>,
     current_level: Res<CurrentLevel>,
     level_assets: Res<Assets<Level>>,
 ) {
-    if let CurrentLevel::Loaded {
-        level_handle,
-        level_index: _,
-    } = &*current_level
+    // The sandbox has no goal, so only campaign and custom levels can be completed
+    if let CurrentLevel::Loaded { level_handle, .. } | CurrentLevel::Custom(level_handle) =
+        &*current_level
     {
         match level_assets
             .get(level_handle)
@@ -115,11 +119,24 @@ fn check_win_condition(
             super::level::LevelGoal::CreateAtom(atom_type) => {
                 if atoms
                     .iter()
-                    .any(|&existing_atom_type| existing_atom_type == atom_type)
+                    .any(|(&existing_atom_type, _)| existing_atom_type == atom_type)
                 {
                     next_state.set(GameState::LevelComplete);
                 }
             }
+            super::level::LevelGoal::FillDetectors => {
+                if detectors.iter().all(Detector::is_satisfied) {
+                    next_state.set(GameState::LevelComplete);
+                }
+            }
+            super::level::LevelGoal::CreateAtomAtDepth { atom_type, depth } => {
+                if atoms.iter().any(|(&existing_atom_type, lineage)| {
+                    existing_atom_type == atom_type
+                        && lineage.is_some_and(|lineage| lineage.depth >= depth)
+                }) {
+                    next_state.set(GameState::LevelComplete);
+                }
+            }
         }
     }
 }
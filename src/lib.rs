@@ -0,0 +1,226 @@
+//! The game, as a library shared by the game itself and the `level_tool` command line tool.
+
+// Support configuring Bevy lints within code.
+#![cfg_attr(bevy_lint, feature(register_tool), register_tool(bevy))]
+
+mod asset_tracking;
+mod audio;
+#[cfg(feature = "dev")]
+mod dev_tools;
+pub mod game;
+mod menus;
+mod save;
+mod screens;
+mod theme;
+
+use bevy::{
+    asset::AssetMetaCheck,
+    prelude::*,
+    render::{camera::ScalingMode, view::RenderLayers},
+};
+use bevy_easings::EasingsPlugin;
+
+pub const LEVELS: [&str; 11] = [
+    "0.ron", "1.ron", "2.ron", "3.ron", "4.ron", "5.ron", "6.ron", "7.ron", "8.ron", "9.ron",
+    "10.ron",
+];
+
+pub struct AppPlugin;
+
+impl Plugin for AppPlugin {
+    fn build(&self, app: &mut App) {
+        // Add Bevy plugins.
+        app.add_plugins(
+            DefaultPlugins
+                .set(AssetPlugin {
+                    // Wasm builds will check for meta files (that don't exist) if this isn't set.
+                    // This causes errors and even panics on web build on itch.
+                    // See https://github.com/bevyengine/bevy_github_ci_template/issues/48.
+                    meta_check: AssetMetaCheck::Never,
+                    ..default()
+                })
+                .set(WindowPlugin {
+                    primary_window: Window {
+                        title: "Bevy Jam 6".to_string(),
+                        fit_canvas_to_parent: true,
+                        ..default()
+                    }
+                    .into(),
+                    ..default()
+                }),
+        );
+        #[cfg(feature = "dev")]
+        app.add_plugins(dev_tools::plugin);
+        add_game(app);
+    }
+}
+
+/// Add everything but Bevy's own plugins and the dev tools. This is shared with the headless app
+/// used by tests.
+fn add_game(app: &mut App) {
+    // Add other plugins.
+    app.add_plugins((
+        asset_tracking::plugin,
+        audio::plugin,
+        // demo::plugin,
+        game::plugin,
+        menus::plugin,
+        save::plugin,
+        screens::plugin,
+        theme::plugin,
+        EasingsPlugin::default(),
+    ));
+
+    // Order new `AppSystems` variants by adding them here:
+    app.configure_sets(
+        Update,
+        (
+            AppSystems::TickTimers,
+            AppSystems::RecordInput,
+            AppSystems::Update,
+        )
+            .chain(),
+    );
+
+    // Set up the `Pause` state.
+    app.init_state::<Pause>();
+    app.configure_sets(Update, PausableSystems.run_if(in_state(Pause(false))));
+
+    // Spawn the main camera.
+    app.add_systems(Startup, (spawn_camera, set_gizmo_config));
+}
+
+/// The game without a window or renderer, ready to be updated. Each update steps time by one 60fps
+/// frame.
+#[cfg(test)]
+pub(crate) fn headless_app() -> App {
+    use std::time::Duration;
+
+    use bevy::{
+        log::LogPlugin,
+        render::{RenderPlugin, settings::WgpuSettings},
+        time::TimeUpdateStrategy,
+        window::ExitCondition,
+        winit::WinitPlugin,
+    };
+
+    let mut app = App::new();
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: None,
+                exit_condition: ExitCondition::DontExit,
+                ..default()
+            })
+            .set(RenderPlugin {
+                render_creation: WgpuSettings {
+                    backends: None,
+                    ..default()
+                }
+                .into(),
+                ..default()
+            })
+            .disable::<WinitPlugin>()
+            .disable::<LogPlugin>(),
+    );
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        1.0 / 60.0,
+    )));
+    add_game(&mut app);
+    app.finish();
+    app.cleanup();
+    app
+}
+
+/// Longest a headless app is updated for while waiting for something, in frames
+#[cfg(test)]
+const MAX_FRAMES: u32 = 60 * 90;
+
+#[cfg(test)]
+pub(crate) fn game_state(app: &App) -> Option<game::state::GameState> {
+    app.world()
+        .get_resource::<State<game::state::GameState>>()
+        .map(|state| *state.get())
+}
+
+/// Update a headless app until the game reaches `state`. Assets load on other threads, so time is
+/// given for them until gameplay starts.
+#[cfg(test)]
+pub(crate) fn run_until(app: &mut App, state: game::state::GameState) -> bool {
+    for _ in 0..MAX_FRAMES {
+        app.update();
+        match game_state(app) {
+            Some(current) if current == state => return true,
+            Some(_) => {}
+            None => std::thread::sleep(std::time::Duration::from_millis(5)),
+        }
+    }
+    false
+}
+
+/// High-level groupings of systems for the app in the `Update` schedule.
+/// When adding a new variant, make sure to order it in the `configure_sets`
+/// call above.
+#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
+enum AppSystems {
+    /// Tick timers.
+    TickTimers,
+    /// Record player input.
+    RecordInput,
+    /// Do everything else (consider splitting this into further variants).
+    Update,
+}
+
+/// Whether or not the game is paused.
+#[derive(States, Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[states(scoped_entities)]
+struct Pause(pub bool);
+
+/// A system set for systems that shouldn't run while the game is paused.
+#[derive(SystemSet, Copy, Clone, Eq, PartialEq, Hash, Debug)]
+struct PausableSystems;
+
+fn spawn_camera(mut commands: Commands) {
+    let camera_width = 16.0;
+    let camera_height = 9.0;
+    // "Bottom" camera (for drawing gizmos behind other sprites)
+    commands.spawn((
+        Name::new("Bottom Camera"),
+        Camera2d,
+        Camera {
+            order: 1,
+            ..Default::default()
+        },
+        Projection::from(OrthographicProjection {
+            scaling_mode: ScalingMode::AutoMin {
+                min_width: camera_width,
+                min_height: camera_height,
+            },
+            ..OrthographicProjection::default_2d()
+        }),
+        RenderLayers::layer(1),
+    ));
+
+    // main camera
+    commands.spawn((
+        Name::new("Main Camera"),
+        Camera2d,
+        Camera {
+            order: 2,
+            ..Default::default()
+        },
+        Projection::from(OrthographicProjection {
+            scaling_mode: ScalingMode::AutoMin {
+                min_width: camera_width,
+                min_height: camera_height,
+            },
+            ..OrthographicProjection::default_2d()
+        }),
+        RenderLayers::layer(2),
+    ));
+}
+
+fn set_gizmo_config(mut config_store: ResMut<GizmoConfigStore>) {
+    let (config, _) = config_store.config_mut::<DefaultGizmoConfigGroup>();
+    config.render_layers = RenderLayers::layer(2);
+}
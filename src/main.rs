@@ -3,153 +3,9 @@
 // Disable console on Windows for non-dev builds.
 #![cfg_attr(not(feature = "dev"), windows_subsystem = "windows")]
 
-mod asset_tracking;
-mod audio;
-#[cfg(feature = "dev")]
-mod dev_tools;
-mod game;
-mod menus;
-mod screens;
-mod theme;
-
-use bevy::{
-    asset::AssetMetaCheck,
-    prelude::*,
-    render::{camera::ScalingMode, view::RenderLayers},
-};
-use bevy_easings::EasingsPlugin;
-
-pub const LEVELS: [&str; 11] = [
-    "0.ron", "1.ron", "2.ron", "3.ron", "4.ron", "5.ron", "6.ron", "7.ron", "8.ron", "9.ron",
-    "10.ron",
-];
+use bevy::prelude::*;
+use bevy_jam_6::AppPlugin;
 
 fn main() -> AppExit {
     App::new().add_plugins(AppPlugin).run()
 }
-
-pub struct AppPlugin;
-
-impl Plugin for AppPlugin {
-    fn build(&self, app: &mut App) {
-        // Add Bevy plugins.
-        app.add_plugins(
-            DefaultPlugins
-                .set(AssetPlugin {
-                    // Wasm builds will check for meta files (that don't exist) if this isn't set.
-                    // This causes errors and even panics on web build on itch.
-                    // See https://github.com/bevyengine/bevy_github_ci_template/issues/48.
-                    meta_check: AssetMetaCheck::Never,
-                    ..default()
-                })
-                .set(WindowPlugin {
-                    primary_window: Window {
-                        title: "Bevy Jam 6".to_string(),
-                        fit_canvas_to_parent: true,
-                        ..default()
-                    }
-                    .into(),
-                    ..default()
-                }),
-        );
-
-        // Add other plugins.
-        app.add_plugins((
-            asset_tracking::plugin,
-            audio::plugin,
-            // demo::plugin,
-            game::plugin,
-            #[cfg(feature = "dev")]
-            dev_tools::plugin,
-            menus::plugin,
-            screens::plugin,
-            theme::plugin,
-            EasingsPlugin::default(),
-        ));
-
-        // Order new `AppSystems` variants by adding them here:
-        app.configure_sets(
-            Update,
-            (
-                AppSystems::TickTimers,
-                AppSystems::RecordInput,
-                AppSystems::Update,
-            )
-                .chain(),
-        );
-
-        // Set up the `Pause` state.
-        app.init_state::<Pause>();
-        app.configure_sets(Update, PausableSystems.run_if(in_state(Pause(false))));
-
-        // Spawn the main camera.
-        app.add_systems(Startup, (spawn_camera, set_gizmo_config));
-    }
-}
-
-/// High-level groupings of systems for the app in the `Update` schedule.
-/// When adding a new variant, make sure to order it in the `configure_sets`
-/// call above.
-#[derive(SystemSet, Debug, Clone, Copy, Eq, PartialEq, Hash, PartialOrd, Ord)]
-enum AppSystems {
-    /// Tick timers.
-    TickTimers,
-    /// Record player input.
-    RecordInput,
-    /// Do everything else (consider splitting this into further variants).
-    Update,
-}
-
-/// Whether or not the game is paused.
-#[derive(States, Copy, Clone, Eq, PartialEq, Hash, Debug, Default)]
-#[states(scoped_entities)]
-struct Pause(pub bool);
-
-/// A system set for systems that shouldn't run while the game is paused.
-#[derive(SystemSet, Copy, Clone, Eq, PartialEq, Hash, Debug)]
-struct PausableSystems;
-
-fn spawn_camera(mut commands: Commands) {
-    let camera_width = 16.0;
-    let camera_height = 9.0;
-    // "Bottom" camera (for drawing gizmos behind other sprites)
-    commands.spawn((
-        Name::new("Bottom Camera"),
-        Camera2d,
-        Camera {
-            order: 1,
-            ..Default::default()
-        },
-        Projection::from(OrthographicProjection {
-            scaling_mode: ScalingMode::AutoMin {
-                min_width: camera_width,
-                min_height: camera_height,
-            },
-            ..OrthographicProjection::default_2d()
-        }),
-        RenderLayers::layer(1),
-    ));
-
-    // main camera
-    commands.spawn((
-        Name::new("Main Camera"),
-        Camera2d,
-        Camera {
-            order: 2,
-            ..Default::default()
-        },
-        Projection::from(OrthographicProjection {
-            scaling_mode: ScalingMode::AutoMin {
-                min_width: camera_width,
-                min_height: camera_height,
-            },
-            ..OrthographicProjection::default_2d()
-        }),
-        RenderLayers::layer(2),
-    ));
-}
-
-fn set_gizmo_config(mut config_store: ResMut<GizmoConfigStore>) {
-    let (config, _) = config_store.config_mut::<DefaultGizmoConfigGroup>();
-    config.render_layers = RenderLayers::layer(2);
-}
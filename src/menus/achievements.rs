@@ -0,0 +1,96 @@
+//! The achievements menu.
+
+use bevy::{ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{
+    game::achievement::ACHIEVEMENTS,
+    menus::Menu,
+    save::SaveData,
+    theme::{palette::*, widget},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Achievements), spawn_achievements_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Achievements).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+fn spawn_achievements_menu(mut commands: Commands, save_data: Res<SaveData>) {
+    let progress = &save_data.achievements;
+    let unlocked_count = ACHIEVEMENTS
+        .iter()
+        .filter(|achievement| progress.is_unlocked(achievement))
+        .count();
+    let rows: Vec<_> = ACHIEVEMENTS
+        .iter()
+        .map(|achievement| {
+            let unlocked = progress.is_unlocked(achievement);
+            let mut description = achievement.description.to_string();
+            if let Some((current, target)) = progress.counter(achievement)
+                && !unlocked
+            {
+                description.push_str(&format!(" ({current}/{target})"));
+            }
+            (achievement.name, description, unlocked)
+        })
+        .collect();
+    commands.spawn((
+        widget::ui_root("Achievements Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Achievements),
+        children![
+            widget::header("Achievements"),
+            widget::label(format!(
+                "{unlocked_count} of {} unlocked",
+                ACHIEVEMENTS.len()
+            )),
+            (
+                Name::new("Achievement List"),
+                Node {
+                    display: Display::Grid,
+                    grid_template_columns: vec![
+                        RepeatedGridTrack::px(1, 260.0),
+                        RepeatedGridTrack::px(1, 480.0),
+                    ],
+                    row_gap: Val::Px(8.0),
+                    column_gap: Val::Px(20.0),
+                    ..default()
+                },
+                Children::spawn(SpawnIter(rows.into_iter().flat_map(
+                    |(name, description, unlocked)| {
+                        let color = if unlocked {
+                            HEADER_TEXT
+                        } else {
+                            OFF_WHITE.with_alpha(0.4)
+                        };
+                        [
+                            (
+                                Name::new("Achievement Name"),
+                                Text::new(name),
+                                TextFont::from_font_size(22.0),
+                                TextColor(color),
+                            ),
+                            (
+                                Name::new("Achievement Description"),
+                                Text::new(description),
+                                TextFont::from_font_size(18.0),
+                                TextColor(if unlocked { LABEL_TEXT } else { color }),
+                            ),
+                        ]
+                    }
+                ))),
+            ),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
@@ -0,0 +1,236 @@
+//! The import code menu, where a share code can be typed or pasted in to play the level it
+//! contains. Web builds open this menu straight away when the page's link contains a code.
+
+use bevy::{
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+    text::LineBreak,
+};
+
+use crate::{
+    asset_tracking::ResourceHandles,
+    game::{
+        MenuSelection,
+        level::{Level, LevelPlacement, PlacedLevelAtoms},
+        share::{Clipboard, SharedLevel},
+    },
+    menus::Menu,
+    screens::Screen,
+    theme::{palette::*, widget},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ImportCode>();
+    app.add_observer(import_level);
+    app.add_systems(
+        OnEnter(Menu::ImportCode),
+        (spawn_import_code_menu, update_import_code_menu).chain(),
+    );
+    app.add_systems(
+        Update,
+        (
+            type_code,
+            update_import_code_menu.run_if(resource_changed::<ImportCode>),
+        )
+            .chain()
+            .run_if(in_state(Menu::ImportCode)),
+    );
+    #[cfg(target_family = "wasm")]
+    {
+        app.add_systems(Startup, read_code_from_url);
+        app.add_systems(
+            OnEnter(Menu::Main),
+            open_linked_code.run_if(resource_exists::<LinkedCode>),
+        );
+    }
+}
+
+/// The code being entered, and why it couldn't be imported
+#[derive(Resource, Default)]
+struct ImportCode {
+    code: String,
+    error: Option<String>,
+}
+
+/// Play the level in the code that has been entered
+#[derive(Event)]
+struct ImportLevel;
+
+#[derive(Component)]
+struct ImportCodeText;
+
+#[derive(Component)]
+struct ImportErrorText;
+
+fn spawn_import_code_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Import Code Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::ImportCode),
+        children![
+            widget::header("Import code"),
+            widget::label("Type or paste a level code, or a link containing one."),
+            (
+                Name::new("Import Code Text"),
+                ImportCodeText,
+                Text::default(),
+                TextFont::from_font_size(14.0),
+                TextColor(OFF_WHITE),
+                TextLayout::new(JustifyText::Center, LineBreak::AnyCharacter),
+                Node {
+                    max_width: Val::Px(700.0),
+                    ..default()
+                },
+            ),
+            (
+                Name::new("Import Error Text"),
+                ImportErrorText,
+                Text::default(),
+                TextFont::from_font_size(18.0),
+                TextColor(LABEL_TEXT),
+            ),
+            (
+                Name::new("Import Code Buttons"),
+                Node {
+                    column_gap: Val::Px(20.0),
+                    ..default()
+                },
+                children![
+                    widget::button("Paste", paste_code),
+                    widget::button("Clear", clear_code),
+                    widget::button("Play", play_code),
+                ],
+            ),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn update_import_code_menu(
+    import_code: Res<ImportCode>,
+    mut code_text: Query<&mut Text, (With<ImportCodeText>, Without<ImportErrorText>)>,
+    mut error_text: Query<&mut Text, (With<ImportErrorText>, Without<ImportCodeText>)>,
+) {
+    for mut text in &mut code_text {
+        text.0 = format!("{}_", import_code.code);
+    }
+    for mut text in &mut error_text {
+        text.0 = import_code.error.clone().unwrap_or_default();
+    }
+}
+
+fn type_code(
+    mut commands: Commands,
+    mut keys: EventReader<KeyboardInput>,
+    modifiers: Res<ButtonInput<KeyCode>>,
+    mut import_code: ResMut<ImportCode>,
+    mut clipboard: ResMut<Clipboard>,
+    mut next_menu: ResMut<NextState<Menu>>,
+) {
+    let shortcut = modifiers.any_pressed([
+        KeyCode::ControlLeft,
+        KeyCode::ControlRight,
+        KeyCode::SuperLeft,
+        KeyCode::SuperRight,
+    ]);
+    for key in keys.read().filter(|key| key.state.is_pressed()) {
+        match &key.logical_key {
+            Key::Character(text) if shortcut && text.eq_ignore_ascii_case("v") => {
+                paste(&mut import_code, &mut clipboard);
+            }
+            // Other shortcuts aren't part of the code
+            Key::Character(_) if shortcut => {}
+            Key::Character(text) => {
+                import_code.code.extend(
+                    text.chars()
+                        .filter(|c| !c.is_control() && !c.is_whitespace()),
+                );
+            }
+            Key::Backspace => {
+                import_code.code.pop();
+            }
+            Key::Enter => commands.trigger(ImportLevel),
+            Key::Escape => next_menu.set(Menu::LevelSelect),
+            _ => {}
+        }
+    }
+}
+
+fn paste(import_code: &mut ImportCode, clipboard: &mut Clipboard) {
+    match clipboard.get_text() {
+        Ok(text) => {
+            import_code.code = text.trim().to_string();
+            import_code.error = None;
+        }
+        Err(err) => import_code.error = Some(err),
+    }
+}
+
+fn paste_code(
+    _: Trigger<Pointer<Click>>,
+    mut import_code: ResMut<ImportCode>,
+    mut clipboard: ResMut<Clipboard>,
+) {
+    paste(&mut import_code, &mut clipboard);
+}
+
+fn clear_code(_: Trigger<Pointer<Click>>, mut import_code: ResMut<ImportCode>) {
+    *import_code = ImportCode::default();
+}
+
+fn play_code(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(ImportLevel);
+}
+
+fn import_level(
+    _: Trigger<ImportLevel>,
+    mut import_code: ResMut<ImportCode>,
+    mut level_assets: ResMut<Assets<Level>>,
+    mut placed_atoms: ResMut<PlacedLevelAtoms>,
+    resource_handles: Res<ResourceHandles>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut menu_selection: ResMut<MenuSelection>,
+) {
+    let shared = match SharedLevel::decode(&import_code.code) {
+        Ok(shared) => shared,
+        Err(err) => {
+            import_code.error = Some(err.to_string());
+            return;
+        }
+    };
+    // Codes with a solution open with its atoms already placed
+    match &shared.solution {
+        Some(solution) => placed_atoms.set_layout(&LevelPlacement::layout(solution)),
+        None => placed_atoms.clear(),
+    }
+    *import_code = ImportCode::default();
+    *menu_selection = MenuSelection::Custom(level_assets.add(shared.level));
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+/// Present until the menu has been opened for the code in the page's link
+#[cfg(target_family = "wasm")]
+#[derive(Resource)]
+struct LinkedCode;
+
+#[cfg(target_family = "wasm")]
+fn read_code_from_url(mut commands: Commands) {
+    if let Some(code) = crate::game::share::code_from_page_url() {
+        commands.insert_resource(ImportCode { code, error: None });
+        commands.insert_resource(LinkedCode);
+    }
+}
+
+#[cfg(target_family = "wasm")]
+fn open_linked_code(mut commands: Commands, mut next_menu: ResMut<NextState<Menu>>) {
+    commands.remove_resource::<LinkedCode>();
+    next_menu.set(Menu::ImportCode);
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::LevelSelect);
+}
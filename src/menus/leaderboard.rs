@@ -0,0 +1,339 @@
+//! The leaderboard menu, showing the best solutions to each level from every local profile.
+
+use bevy::{
+    ecs::{spawn::SpawnWith, system::IntoObserverSystem},
+    input::keyboard::{Key, KeyboardInput},
+    prelude::*,
+    ui::Val::*,
+};
+
+use crate::{
+    LEVELS,
+    game::{
+        leaderboard::{LEADERBOARD_SIZE, Leaderboard, LeaderboardCategory, ReplayLayout},
+        level::PlacedAtom,
+    },
+    menus::Menu,
+    save::{MAX_PROFILE_NAME_LENGTH, SaveData},
+    theme::{palette::*, widget},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<LeaderboardLevel>();
+    app.add_systems(
+        OnEnter(Menu::Leaderboard),
+        (spawn_leaderboard_menu, update_leaderboard_menu).chain(),
+    );
+    app.add_systems(OnExit(Menu::Leaderboard), stop_renaming);
+    app.add_systems(
+        Update,
+        (
+            rename_profile.run_if(resource_exists::<RenamingProfile>),
+            update_leaderboard_menu.run_if(
+                resource_changed::<LeaderboardLevel>
+                    .or(resource_changed::<SaveData>)
+                    .or(resource_added::<RenamingProfile>)
+                    .or(resource_removed::<RenamingProfile>),
+            ),
+        )
+            .chain()
+            .run_if(in_state(Menu::Leaderboard)),
+    );
+}
+
+/// Index of the level whose leaderboard is being shown
+#[derive(Resource, Default)]
+struct LeaderboardLevel(usize);
+
+/// Present while the player is typing a new name for their profile
+#[derive(Resource)]
+struct RenamingProfile;
+
+#[derive(Component)]
+struct LeaderboardMenuContent;
+
+fn spawn_leaderboard_menu(mut commands: Commands) {
+    commands.spawn((
+        widget::ui_root("Leaderboard Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Leaderboard),
+        children![
+            widget::header("Leaderboards"),
+            (
+                Name::new("Leaderboard Menu Content"),
+                LeaderboardMenuContent,
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Px(16.0),
+                    ..default()
+                },
+            ),
+            (
+                Name::new("Profile Buttons"),
+                Node {
+                    column_gap: Px(20.0),
+                    ..default()
+                },
+                children![
+                    widget::button("New profile", add_profile),
+                    widget::button("Rename", toggle_renaming),
+                ],
+            ),
+            widget::button("Back", go_back),
+        ],
+    ));
+}
+
+fn update_leaderboard_menu(
+    mut commands: Commands,
+    level: Res<LeaderboardLevel>,
+    save_data: Res<SaveData>,
+    renaming: Option<Res<RenamingProfile>>,
+    containers: Query<Entity, With<LeaderboardMenuContent>>,
+) {
+    let leaderboard = save_data
+        .level(LEVELS[level.0])
+        .map(|progress| progress.leaderboard.clone())
+        .unwrap_or_default();
+    let mut profile_name = save_data.profile_name().to_string();
+    if renaming.is_some() {
+        profile_name.push('_');
+    }
+    for container in &containers {
+        commands
+            .entity(container)
+            .despawn_related::<Children>()
+            .with_children(|parent| {
+                parent.spawn(selector(
+                    format!("Level {}", level.0 + 1),
+                    previous_level,
+                    next_level,
+                ));
+                parent.spawn(selector(
+                    format!("Profile: {profile_name}"),
+                    previous_profile,
+                    next_profile,
+                ));
+                parent.spawn(leaderboard_table(level.0, &leaderboard, LEADERBOARD_SIZE));
+            });
+    }
+}
+
+/// A label with buttons either side to step through options
+fn selector<E1, B1, M1, E2, B2, M2>(
+    text: String,
+    previous: impl IntoObserverSystem<E1, B1, M1>,
+    next: impl IntoObserverSystem<E2, B2, M2>,
+) -> impl Bundle
+where
+    E1: Event,
+    B1: Bundle,
+    E2: Event,
+    B2: Bundle,
+{
+    (
+        Name::new("Selector"),
+        Node {
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        children![
+            widget::button_small("<", previous),
+            (
+                Name::new("Selector Label"),
+                Node {
+                    width: Px(360.0),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                children![widget::label(text)],
+            ),
+            widget::button_small(">", next),
+        ],
+    )
+}
+
+/// The best entries for a level in each category. Clicking an entry replays its layout.
+pub(super) fn leaderboard_table(
+    level_index: usize,
+    leaderboard: &Leaderboard,
+    rows: usize,
+) -> impl Bundle {
+    let columns: Vec<_> = LeaderboardCategory::ALL
+        .into_iter()
+        .map(|category| {
+            let entries: Vec<_> = leaderboard
+                .top(category)
+                .into_iter()
+                .take(rows)
+                .map(|entry| {
+                    (
+                        format!("{} - {}", entry.profile, category.describe(entry)),
+                        entry.layout.clone(),
+                    )
+                })
+                .collect();
+            (category.title(), entries)
+        })
+        .collect();
+    (
+        Name::new("Leaderboard Table"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Px(8.0),
+            ..default()
+        },
+        Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+            parent.spawn((
+                Name::new("Leaderboard Hint"),
+                Text::new("Click an entry to replay it"),
+                TextFont::from_font_size(14.0),
+                TextColor(LABEL_TEXT),
+            ));
+            parent
+                .spawn((
+                    Name::new("Leaderboard Columns"),
+                    Node {
+                        column_gap: Px(30.0),
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    for (title, entries) in columns {
+                        leaderboard_column(parent, level_index, title, entries);
+                    }
+                });
+        })),
+    )
+}
+
+fn leaderboard_column(
+    parent: &mut ChildSpawner,
+    level_index: usize,
+    title: &str,
+    entries: Vec<(String, Vec<(IVec2, PlacedAtom)>)>,
+) {
+    parent
+        .spawn((
+            Name::new("Leaderboard Column"),
+            Node {
+                flex_direction: FlexDirection::Column,
+                width: Px(240.0),
+                row_gap: Px(4.0),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("Leaderboard Column Title"),
+                Text::new(title),
+                TextFont::from_font_size(20.0),
+                TextColor(HEADER_TEXT),
+            ));
+            if entries.is_empty() {
+                parent.spawn((
+                    Name::new("Leaderboard Empty"),
+                    Text::new("No solutions yet"),
+                    TextFont::from_font_size(16.0),
+                    TextColor(LABEL_TEXT),
+                ));
+            }
+            for (rank, (text, layout)) in entries.into_iter().enumerate() {
+                parent
+                    .spawn((
+                        Name::new("Leaderboard Entry"),
+                        Button,
+                        Text::new(format!("{}. {text}", rank + 1)),
+                        TextFont::from_font_size(16.0),
+                        TextColor(OFF_WHITE),
+                    ))
+                    .observe(move |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                        commands.trigger(ReplayLayout {
+                            level_index,
+                            layout: layout.clone(),
+                        });
+                    });
+            }
+        });
+}
+
+fn previous_level(_: Trigger<Pointer<Click>>, mut level: ResMut<LeaderboardLevel>) {
+    level.0 = (level.0 + LEVELS.len() - 1) % LEVELS.len();
+}
+
+fn next_level(_: Trigger<Pointer<Click>>, mut level: ResMut<LeaderboardLevel>) {
+    level.0 = (level.0 + 1) % LEVELS.len();
+}
+
+fn previous_profile(_: Trigger<Pointer<Click>>, mut save_data: ResMut<SaveData>) {
+    save_data.cycle_profile(-1);
+    save_data.save();
+}
+
+fn next_profile(_: Trigger<Pointer<Click>>, mut save_data: ResMut<SaveData>) {
+    save_data.cycle_profile(1);
+    save_data.save();
+}
+
+fn add_profile(_: Trigger<Pointer<Click>>, mut save_data: ResMut<SaveData>) {
+    save_data.add_profile();
+    save_data.save();
+}
+
+fn toggle_renaming(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    renaming: Option<Res<RenamingProfile>>,
+    save_data: Res<SaveData>,
+) {
+    if renaming.is_some() {
+        commands.remove_resource::<RenamingProfile>();
+        save_data.save();
+    } else {
+        commands.insert_resource(RenamingProfile);
+    }
+}
+
+fn stop_renaming(mut commands: Commands, save_data: Res<SaveData>) {
+    commands.remove_resource::<RenamingProfile>();
+    save_data.save();
+}
+
+fn rename_profile(
+    mut commands: Commands,
+    mut keys: EventReader<KeyboardInput>,
+    mut save_data: ResMut<SaveData>,
+) {
+    for key in keys.read().filter(|key| key.state.is_pressed()) {
+        match &key.logical_key {
+            Key::Character(text) => {
+                let name = save_data.profile_name_mut();
+                for c in text.chars().filter(|c| !c.is_control()) {
+                    if name.chars().count() < MAX_PROFILE_NAME_LENGTH {
+                        name.push(c);
+                    }
+                }
+            }
+            Key::Space => {
+                let name = save_data.profile_name_mut();
+                if name.chars().count() < MAX_PROFILE_NAME_LENGTH {
+                    name.push(' ');
+                }
+            }
+            Key::Backspace => {
+                save_data.profile_name_mut().pop();
+            }
+            Key::Enter | Key::Escape => {
+                commands.remove_resource::<RenamingProfile>();
+                save_data.save();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn go_back(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::LevelSelect);
+}
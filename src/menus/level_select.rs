@@ -1,15 +1,65 @@
 use bevy::{ecs::spawn::SpawnIter, prelude::*};
 
 use crate::{
-    LEVELS, asset_tracking::ResourceHandles, game::MenuSelection, menus::Menu, screens::Screen,
-    theme::widget,
+    LEVELS,
+    asset_tracking::ResourceHandles,
+    game::{
+        MenuSelection,
+        difficulty::LevelDifficulties,
+        generator::{Difficulty, generate_level},
+        level::Level,
+    },
+    menus::Menu,
+    save::SaveData,
+    screens::Screen,
+    theme::{palette::LABEL_TEXT, widget},
 };
 
 pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GeneratorDifficulty>();
     app.add_systems(OnEnter(Menu::LevelSelect), spawn_level_select_menu);
+    app.add_systems(
+        Update,
+        update_difficulty_label.run_if(in_state(Menu::LevelSelect)),
+    );
 }
 
-fn spawn_level_select_menu(mut commands: Commands) {
+/// Difficulty of the next generated puzzle
+#[derive(Resource)]
+struct GeneratorDifficulty(Difficulty);
+
+impl Default for GeneratorDifficulty {
+    fn default() -> Self {
+        Self(Difficulty::Medium)
+    }
+}
+
+#[derive(Component)]
+struct DifficultyLabel;
+
+fn spawn_level_select_menu(
+    mut commands: Commands,
+    save_data: Res<SaveData>,
+    difficulties: Option<Res<LevelDifficulties>>,
+) {
+    let stars: Vec<u8> = LEVELS
+        .iter()
+        .map(|level_file| {
+            save_data
+                .level(level_file)
+                .map_or(0, |progress| progress.stars)
+        })
+        .collect();
+    // Levels are only rated once they've loaded
+    let badges: Vec<String> = (0..LEVELS.len())
+        .map(|index| {
+            difficulties
+                .as_ref()
+                .and_then(|difficulties| difficulties.0.get(index)?.as_ref())
+                .map(|estimate| estimate.rating_name().to_string())
+                .unwrap_or_default()
+        })
+        .collect();
     commands.spawn((
         widget::ui_root("Level Select Menu"),
         GlobalZIndex(2),
@@ -27,8 +77,18 @@ fn spawn_level_select_menu(mut commands: Commands) {
                     column_gap: Val::Px(5.0),
                     ..Default::default()
                 },
-                Children::spawn((SpawnIter(LEVELS.iter().enumerate().map(|(i, _)| {
-                    (widget::button(
+                Children::spawn((SpawnIter(stars.into_iter().zip(badges).enumerate().map(
+                    |(i, (stars, badge))| {
+                        (
+                            Name::new("Level Button"),
+                            Node {
+                                flex_direction: FlexDirection::Column,
+                                align_items: AlignItems::Center,
+                                row_gap: Val::Px(4.0),
+                                ..Default::default()
+                            },
+                            children![
+                            widget::button(
                         (i + 1).to_string(),
                         move |_: Trigger<Pointer<Click>>,
                               resource_handles: Res<ResourceHandles>,
@@ -41,10 +101,42 @@ fn spawn_level_select_menu(mut commands: Commands) {
                                 next_screen.set(Screen::Loading);
                             }
                         },
-                    ),)
-                })),)),
+                    ),
+                            widget::stars(stars, 16.0),
+                            (
+                                Name::new("Difficulty Badge"),
+                                Text(badge),
+                                TextFont::from_font_size(12.0),
+                                TextColor(LABEL_TEXT),
+                            ),
+                        ],
+                        )
+                    },
+                )),)),
+            )),
+            Spawn(generator_row()),
+            Spawn((
+                Name::new("Records Row"),
+                Node {
+                    column_gap: Val::Px(20.0),
+                    ..Default::default()
+                },
+                children![
+                    widget::button("Leaderboards", open_leaderboards),
+                    widget::button("Replays", open_replays),
+                ],
+            )),
+            Spawn((
+                Name::new("Custom Level Row"),
+                Node {
+                    column_gap: Val::Px(20.0),
+                    ..Default::default()
+                },
+                children![
+                    widget::button("Sandbox", start_with_level_editor),
+                    widget::button("Import code", open_import_code),
+                ],
             )),
-            Spawn(widget::button("Sandbox", start_with_level_editor)),
             Spawn(widget::button("Back", go_back)),
         )),
     ));
@@ -64,6 +156,89 @@ fn start_with_level_editor(
     }
 }
 
+fn generator_row() -> impl Bundle {
+    (
+        Name::new("Generator Row"),
+        Node {
+            align_items: AlignItems::Center,
+            column_gap: Val::Px(10.0),
+            ..Default::default()
+        },
+        children![
+            widget::button("Generate puzzle", generate_puzzle),
+            widget::button_small("<", easier),
+            (
+                Name::new("Difficulty"),
+                Node {
+                    width: Val::Px(100.0),
+                    justify_content: JustifyContent::Center,
+                    ..Default::default()
+                },
+                children![(widget::label(""), DifficultyLabel)],
+            ),
+            widget::button_small(">", harder),
+        ],
+    )
+}
+
+fn easier(_: Trigger<Pointer<Click>>, mut difficulty: ResMut<GeneratorDifficulty>) {
+    cycle_difficulty(&mut difficulty.0, -1);
+}
+
+fn harder(_: Trigger<Pointer<Click>>, mut difficulty: ResMut<GeneratorDifficulty>) {
+    cycle_difficulty(&mut difficulty.0, 1);
+}
+
+fn cycle_difficulty(difficulty: &mut Difficulty, offset: isize) {
+    let index = Difficulty::ALL
+        .iter()
+        .position(|other| other == difficulty)
+        .unwrap_or_default() as isize;
+    *difficulty =
+        Difficulty::ALL[(index + offset).rem_euclid(Difficulty::ALL.len() as isize) as usize];
+}
+
+fn update_difficulty_label(
+    difficulty: Res<GeneratorDifficulty>,
+    mut label: Single<&mut Text, With<DifficultyLabel>>,
+) {
+    label.0 = difficulty.0.name().to_string();
+}
+
+fn generate_puzzle(
+    _: Trigger<Pointer<Click>>,
+    difficulty: Res<GeneratorDifficulty>,
+    mut level_assets: ResMut<Assets<Level>>,
+    resource_handles: Res<ResourceHandles>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut menu_selection: ResMut<MenuSelection>,
+) {
+    // Small seeds are easier to share
+    let seed = u64::from(rand::random::<u32>());
+    let Some(level) = generate_level(seed, difficulty.0) else {
+        warn!("Couldn't generate a puzzle from seed {seed}");
+        return;
+    };
+    *menu_selection = MenuSelection::Custom(level_assets.add(level));
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
+fn open_leaderboards(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Leaderboard);
+}
+
+fn open_replays(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Replays);
+}
+
+fn open_import_code(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::ImportCode);
+}
+
 fn go_back(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Main);
 }
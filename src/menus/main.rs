@@ -7,10 +7,15 @@ use bevy_easings::Ease;
 
 use crate::{
     asset_tracking::{LoadResource, ResourceHandles},
-    game::MenuSelection,
+    game::{
+        MenuSelection,
+        daily::{DailyChallenge, DailyProgress, daily_level, format_day, today},
+        level::Level,
+    },
     menus::Menu,
+    save::SaveData,
     screens::Screen,
-    theme::widget,
+    theme::{palette::LABEL_TEXT, widget},
 };
 
 pub(super) fn plugin(app: &mut App) {
@@ -34,7 +39,7 @@ impl FromWorld for MainMenuAssets {
     }
 }
 
-fn spawn_main_menu(mut commands: Commands, assets: Res<MainMenuAssets>) {
+fn spawn_main_menu(mut commands: Commands, assets: Res<MainMenuAssets>, save_data: Res<SaveData>) {
     let title_bundle = (
         Name::new("Title"),
         Node::default(),
@@ -66,8 +71,9 @@ fn spawn_main_menu(mut commands: Commands, assets: Res<MainMenuAssets>) {
             title_bundle,
             widget::button("Start", start_game),
             widget::button("Level Select", open_level_select_menu),
-            widget::button("Settings", open_settings_menu),
-            widget::button("Credits", open_credits_menu),
+            daily_challenge(&save_data.daily),
+            button_row(),
+            widget::button("Achievements", open_achievements_menu),
             widget::button("Exit", exit_app),
         ],
         #[cfg(target_family = "wasm")]
@@ -75,10 +81,60 @@ fn spawn_main_menu(mut commands: Commands, assets: Res<MainMenuAssets>) {
             title_bundle,
             widget::button("Start", start_game),
             widget::button("Level Select", open_level_select_menu),
+            daily_challenge(&save_data.daily),
+            button_row(),
+            widget::button("Achievements", open_achievements_menu),
+        ],
+    ));
+}
+
+/// Secondary menus share a row to keep the menu short enough for small windows
+fn button_row() -> impl Bundle {
+    (
+        Name::new("Button Row"),
+        Node {
+            column_gap: Val::Px(20.0),
+            ..Default::default()
+        },
+        children![
             widget::button("Settings", open_settings_menu),
             widget::button("Credits", open_credits_menu),
+            widget::button("Statistics", open_statistics_menu),
         ],
-    ));
+    )
+}
+
+/// The daily challenge button, with whether today's challenge is done and the current streak
+fn daily_challenge(progress: &DailyProgress) -> impl Bundle {
+    let day = today();
+    let streak = match progress.streak(day) {
+        0 => String::new(),
+        1 => " Streak: 1 day".to_string(),
+        streak => format!(" Streak: {streak} days"),
+    };
+    let status = if progress.is_completed(day) {
+        format!("Done for today!{streak}")
+    } else {
+        format!("A new puzzle every day.{streak}")
+    };
+    (
+        Name::new("Daily Challenge"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(4.0),
+            ..Default::default()
+        },
+        children![
+            widget::button("Daily Challenge", start_daily_challenge),
+            (
+                Name::new("Daily Challenge Status"),
+                Text(status),
+                TextFont::from_font_size(16.0),
+                TextColor(LABEL_TEXT),
+            ),
+        ],
+    )
 }
 
 fn start_game(
@@ -95,6 +151,32 @@ fn start_game(
     }
 }
 
+fn start_daily_challenge(
+    _: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    mut level_assets: ResMut<Assets<Level>>,
+    resource_handles: Res<ResourceHandles>,
+    mut next_screen: ResMut<NextState<Screen>>,
+    mut menu_selection: ResMut<MenuSelection>,
+) {
+    let day = today();
+    let Some(level) = daily_level(day) else {
+        warn!(
+            "Couldn't generate the daily challenge for {}",
+            format_day(day)
+        );
+        return;
+    };
+    let level = level_assets.add(level);
+    *menu_selection = MenuSelection::Custom(level.clone());
+    commands.insert_resource(DailyChallenge { day, level });
+    if resource_handles.is_all_done() {
+        next_screen.set(Screen::Gameplay);
+    } else {
+        next_screen.set(Screen::Loading);
+    }
+}
+
 fn open_settings_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::Settings);
 }
@@ -103,6 +185,14 @@ fn open_credits_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState
     next_menu.set(Menu::Credits);
 }
 
+fn open_statistics_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Statistics);
+}
+
+fn open_achievements_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Achievements);
+}
+
 fn open_level_select_menu(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
     next_menu.set(Menu::LevelSelect);
 }
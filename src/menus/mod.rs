@@ -1,11 +1,16 @@
 //! The game's menus and transitions between them.
 
+mod achievements;
 mod credits;
+mod import_code;
+mod leaderboard;
 mod level_select;
 mod main;
 mod next_level;
 mod pause;
+mod replays;
 mod settings;
+mod statistics;
 
 use bevy::prelude::*;
 
@@ -13,12 +18,17 @@ pub(super) fn plugin(app: &mut App) {
     app.init_state::<Menu>();
 
     app.add_plugins((
+        achievements::plugin,
         credits::plugin,
+        import_code::plugin,
         main::plugin,
         settings::plugin,
+        statistics::plugin,
         pause::plugin,
         next_level::plugin,
         level_select::plugin,
+        leaderboard::plugin,
+        replays::plugin,
     ));
 }
 
@@ -32,4 +42,9 @@ pub enum Menu {
     Settings,
     Pause,
     LevelSelect,
+    Leaderboard,
+    Replays,
+    ImportCode,
+    Achievements,
+    Statistics,
 }
@@ -1,17 +1,29 @@
 use bevy::{prelude::*, render::render_resource::encase::private::Length};
 
 use crate::{
+    LEVELS,
     audio::{AudioAssets, sound_effect},
     game::{
         level::{CurrentLevel, Level, LevelAssets, PlacedLevelAtoms},
+        replay::SaveReplay,
+        score::{LevelScore, record_level_score},
+        share::CopyShareCode,
         state::GameState,
     },
+    menus::leaderboard::leaderboard_table,
+    save::SaveData,
     screens::Screen,
-    theme::widget,
+    theme::{palette::LABEL_TEXT, widget},
 };
 
+/// Leaderboard entries shown per category on the level complete screen
+const LEADERBOARD_ROWS: usize = 3;
+
 pub(super) fn plugin(app: &mut App) {
-    app.add_systems(OnEnter(GameState::LevelComplete), spawn_next_level_menu);
+    app.add_systems(
+        OnEnter(GameState::LevelComplete),
+        spawn_next_level_menu.after(record_level_score),
+    );
 }
 
 fn spawn_next_level_menu(
@@ -19,22 +31,58 @@ fn spawn_next_level_menu(
     audio_assets: Res<AudioAssets>,
     current_level: Res<CurrentLevel>,
     level_assets: Res<Assets<Level>>,
+    score: Res<LevelScore>,
+    save_data: Res<SaveData>,
 ) {
     let text = current_level
         .get_level(&level_assets)
         .expect("Level is initialised at this point")
         .level_complete_text
         .clone();
-    commands.spawn((
-        widget::bouncy_ui_root("Next Level Menu"),
-        GlobalZIndex(2),
-        StateScoped(GameState::LevelComplete),
-        children![
-            widget::header("Level complete!"),
-            widget::label(text),
-            widget::button("Continue", goto_next_level),
-        ],
-    ));
+    let leaderboard = current_level.get_index().map(|index| {
+        let leaderboard = save_data
+            .level(LEVELS[index])
+            .map(|progress| progress.leaderboard.clone())
+            .unwrap_or_default();
+        (index, leaderboard)
+    });
+    commands
+        .spawn((
+            widget::bouncy_ui_root("Next Level Menu"),
+            GlobalZIndex(2),
+            StateScoped(GameState::LevelComplete),
+        ))
+        .with_children(|parent| {
+            parent.spawn(widget::header("Level complete!"));
+            parent.spawn(widget::label(text));
+            parent.spawn(widget::stars(score.stars, 40.0));
+            parent.spawn((
+                Name::new("Score"),
+                Text(score_text(&score)),
+                TextFont::from_font_size(18.0),
+                TextColor(LABEL_TEXT),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+            if let Some((index, leaderboard)) = &leaderboard {
+                parent.spawn(leaderboard_table(*index, leaderboard, LEADERBOARD_ROWS));
+            }
+            parent
+                .spawn((
+                    Name::new("Next Level Buttons"),
+                    Node {
+                        column_gap: Val::Px(20.0),
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    // Only campaign levels can be replayed
+                    if leaderboard.is_some() {
+                        parent.spawn(widget::button("Save replay", save_replay));
+                    }
+                    parent.spawn(widget::button("Share solution", share_solution));
+                    parent.spawn(widget::button("Continue", goto_next_level));
+                });
+        });
     commands.spawn((
         Name::new("Menu Overlay"),
         Node {
@@ -49,6 +97,30 @@ fn spawn_next_level_menu(
     commands.spawn(sound_effect(audio_assets.level_complete_sfx.clone()));
 }
 
+fn score_text(score: &LevelScore) -> String {
+    let line = |name: &str, value: u32, par: Option<u32>| match par {
+        Some(par) => format!("{name}: {value} (par {par})"),
+        None => format!("{name}: {value}"),
+    };
+    [
+        line("Atoms placed", score.atoms_placed, score.par.atoms),
+        line("Ticks", score.ticks, score.par.ticks),
+        line("Reactions", score.reactions, score.par.reactions),
+        line("Hints used", score.hints_used, score.par.hints),
+    ]
+    .join("\n")
+}
+
+fn save_replay(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(SaveReplay);
+}
+
+fn share_solution(_: Trigger<Pointer<Click>>, mut commands: Commands) {
+    commands.trigger(CopyShareCode {
+        with_solution: true,
+    });
+}
+
 fn goto_next_level(
     _: Trigger<Pointer<Click>>,
     mut next_game_state: ResMut<NextState<GameState>>,
@@ -58,11 +130,12 @@ fn goto_next_level(
     level_handles: Res<LevelAssets>,
 ) {
     placed_atoms.clear();
-    let new_index = current_level.get_index().unwrap() + 1;
-    if new_index >= level_handles.levels.length() {
-        next_screen.set(Screen::Title);
-    } else {
-        current_level.set_level(level_handles.levels[new_index].clone(), new_index);
-        next_game_state.set(GameState::Placement);
+    match current_level.get_index().map(|index| index + 1) {
+        Some(new_index) if new_index < level_handles.levels.length() => {
+            current_level.set_level(level_handles.levels[new_index].clone(), new_index);
+            next_game_state.set(GameState::Placement);
+        }
+        // The campaign is over, or the level wasn't part of it
+        _ => next_screen.set(Screen::Title),
     }
 }
@@ -0,0 +1,103 @@
+//! The replays menu, listing saved replays. Replays that no longer match their level or the rules
+//! are shown with the reason they can't be played.
+
+use bevy::{ecs::spawn::SpawnWith, input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{
+    game::{
+        level::{Level, LevelAssets},
+        replay::{PlayReplay, ReplayLibrary, check_replay},
+    },
+    menus::Menu,
+    theme::{palette::*, widget},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Replays), spawn_replays_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Replays).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+/// Most replays listed, newest first
+const REPLAYS_SHOWN: usize = 10;
+
+fn spawn_replays_menu(
+    mut commands: Commands,
+    library: Res<ReplayLibrary>,
+    level_handles: Option<Res<LevelAssets>>,
+    level_assets: Res<Assets<Level>>,
+) {
+    let rows: Vec<_> = library
+        .0
+        .iter()
+        .rev()
+        .take(REPLAYS_SHOWN)
+        .map(|replay| {
+            let status = check_replay(replay, level_handles.as_deref(), &level_assets)
+                .map_err(|err| err.to_string());
+            (replay.clone(), status)
+        })
+        .collect();
+    commands.spawn((
+        widget::ui_root("Replays Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Replays),
+        children![
+            widget::header("Replays"),
+            (
+                Name::new("Replay List"),
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.0),
+                    ..default()
+                },
+                Children::spawn(SpawnWith(move |parent: &mut ChildSpawner| {
+                    if rows.is_empty() {
+                        parent.spawn(widget::label(
+                            "No replays yet. Save one from the sidebar after an experiment.",
+                        ));
+                    }
+                    for (replay, status) in rows {
+                        match status {
+                            Ok(()) => {
+                                parent
+                                    .spawn((
+                                        Name::new("Replay"),
+                                        Button,
+                                        Text::new(replay.describe()),
+                                        TextFont::from_font_size(20.0),
+                                        TextColor(OFF_WHITE),
+                                    ))
+                                    .observe(
+                                        move |_: Trigger<Pointer<Click>>, mut commands: Commands| {
+                                            commands.trigger(PlayReplay(replay.clone()));
+                                        },
+                                    );
+                            }
+                            Err(reason) => {
+                                parent.spawn((
+                                    Name::new("Refused Replay"),
+                                    Text::new(format!("{} ({reason})", replay.describe())),
+                                    TextFont::from_font_size(20.0),
+                                    TextColor(OFF_WHITE.with_alpha(0.4)),
+                                ));
+                            }
+                        }
+                    }
+                })),
+            ),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::LevelSelect);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::LevelSelect);
+}
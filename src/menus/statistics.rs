@@ -0,0 +1,147 @@
+//! The statistics menu, showing lifetime totals from the save file.
+
+use std::collections::BTreeMap;
+
+use bevy::{ecs::spawn::SpawnIter, input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{
+    game::statistics::Statistics,
+    menus::Menu,
+    save::SaveData,
+    theme::{palette::*, widget},
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(Menu::Statistics), spawn_statistics_menu);
+    app.add_systems(
+        Update,
+        go_back.run_if(in_state(Menu::Statistics).and(input_just_pressed(KeyCode::Escape))),
+    );
+}
+
+fn spawn_statistics_menu(mut commands: Commands, save_data: Res<SaveData>) {
+    let statistics = &save_data.statistics;
+    commands.spawn((
+        widget::ui_root("Statistics Menu"),
+        GlobalZIndex(2),
+        StateScoped(Menu::Statistics),
+        children![
+            widget::header("Statistics"),
+            (
+                Name::new("Statistics Columns"),
+                Node {
+                    column_gap: Val::Px(60.0),
+                    align_items: AlignItems::Start,
+                    ..default()
+                },
+                children![
+                    statistics_table("General", general_rows(statistics)),
+                    statistics_table("Atoms placed", count_rows(&statistics.atoms_placed)),
+                    statistics_table("Reactions", count_rows(&statistics.reactions)),
+                ],
+            ),
+            widget::button("Back", go_back_on_click),
+        ],
+    ));
+}
+
+fn general_rows(statistics: &Statistics) -> Vec<(String, String)> {
+    let play_time = statistics.play_time_secs as u64;
+    vec![
+        (
+            "Experiments run".to_string(),
+            statistics.experiments_run.to_string(),
+        ),
+        (
+            "Levels completed".to_string(),
+            statistics.levels_completed.to_string(),
+        ),
+        (
+            "Atoms placed".to_string(),
+            statistics.total_atoms_placed().to_string(),
+        ),
+        (
+            "Reactions".to_string(),
+            statistics.total_reactions().to_string(),
+        ),
+        ("Resets".to_string(), statistics.resets.to_string()),
+        (
+            "Play time".to_string(),
+            format!(
+                "{}:{:02}:{:02}",
+                play_time / 3600,
+                play_time / 60 % 60,
+                play_time % 60
+            ),
+        ),
+    ]
+}
+
+/// Rows for a set of counters, most common first
+fn count_rows(counts: &BTreeMap<String, u32>) -> Vec<(String, String)> {
+    let mut counts: Vec<_> = counts.iter().collect();
+    counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+    counts
+        .into_iter()
+        .map(|(name, count)| (name.clone(), count.to_string()))
+        .collect()
+}
+
+/// A titled two-column table of names and values
+fn statistics_table(title: &'static str, rows: Vec<(String, String)>) -> impl Bundle {
+    let rows = if rows.is_empty() {
+        vec![("None yet".to_string(), String::new())]
+    } else {
+        rows
+    };
+    (
+        Name::new("Statistics Table"),
+        Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(10.0),
+            ..default()
+        },
+        children![
+            (
+                Name::new("Statistics Table Title"),
+                Text::new(title),
+                TextFont::from_font_size(24.0),
+                TextColor(HEADER_TEXT),
+            ),
+            (
+                Name::new("Statistics Table Rows"),
+                Node {
+                    display: Display::Grid,
+                    grid_template_columns: RepeatedGridTrack::auto(2),
+                    row_gap: Val::Px(4.0),
+                    column_gap: Val::Px(20.0),
+                    ..default()
+                },
+                Children::spawn(SpawnIter(rows.into_iter().flat_map(|(name, value)| {
+                    [
+                        (
+                            Name::new("Statistic Name"),
+                            Text::new(name),
+                            TextFont::from_font_size(18.0),
+                            TextColor(LABEL_TEXT),
+                        ),
+                        (
+                            Name::new("Statistic Value"),
+                            Text::new(value),
+                            TextFont::from_font_size(18.0),
+                            TextColor(OFF_WHITE),
+                        ),
+                    ]
+                }))),
+            ),
+        ],
+    )
+}
+
+fn go_back_on_click(_: Trigger<Pointer<Click>>, mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
+
+fn go_back(mut next_menu: ResMut<NextState<Menu>>) {
+    next_menu.set(Menu::Main);
+}
@@ -0,0 +1,135 @@
+//! Persistent player progress, saved as RON alongside the game.
+//!
+//! Web builds have nowhere to write files, so progress only lasts for the current session there.
+//! Tests don't read or write the save file either.
+
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{
+    achievement::AchievementProgress, daily::DailyProgress, leaderboard::Leaderboard,
+    statistics::Statistics,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(SaveData::load());
+}
+
+#[cfg(not(any(target_family = "wasm", test)))]
+const SAVE_PATH: &str = "save.ron";
+
+/// Name used until the player creates or renames a profile
+const DEFAULT_PROFILE: &str = "Player";
+
+/// Longest allowed profile name, in characters
+pub const MAX_PROFILE_NAME_LENGTH: usize = 16;
+
+#[derive(Resource, Debug, Default, Serialize, Deserialize)]
+pub struct SaveData {
+    /// Progress for each campaign level, keyed by level file name
+    #[serde(default)]
+    pub levels: BTreeMap<String, LevelProgress>,
+    /// Names of everyone playing on this machine, for the leaderboards
+    #[serde(default)]
+    pub profiles: Vec<String>,
+    /// Index into `profiles` of whoever is playing now
+    #[serde(default)]
+    pub current_profile: usize,
+    #[serde(default)]
+    pub achievements: AchievementProgress,
+    #[serde(default)]
+    pub statistics: Statistics,
+    #[serde(default)]
+    pub daily: DailyProgress,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LevelProgress {
+    pub completed: bool,
+    /// Best star rating achieved, from 1 to 3
+    #[serde(default)]
+    pub stars: u8,
+    #[serde(default)]
+    pub leaderboard: Leaderboard,
+    /// Most hints revealed in a single attempt
+    #[serde(default)]
+    pub hints_used: u32,
+}
+
+impl SaveData {
+    /// Progress for the given level file, if it has been attempted
+    pub fn level(&self, level_file: &str) -> Option<&LevelProgress> {
+        self.levels.get(level_file)
+    }
+
+    pub fn level_mut(&mut self, level_file: &str) -> &mut LevelProgress {
+        self.levels.entry(level_file.to_string()).or_default()
+    }
+
+    /// Name of the profile currently playing
+    pub fn profile_name(&self) -> &str {
+        self.profiles
+            .get(self.current_profile)
+            .map_or(DEFAULT_PROFILE, String::as_str)
+    }
+
+    /// Name of the profile currently playing, creating the default profile if there are none
+    pub fn profile_name_mut(&mut self) -> &mut String {
+        if self.profiles.is_empty() {
+            self.profiles.push(DEFAULT_PROFILE.to_string());
+        }
+        self.current_profile = self.current_profile.min(self.profiles.len() - 1);
+        &mut self.profiles[self.current_profile]
+    }
+
+    /// Add a new profile and switch to it
+    pub fn add_profile(&mut self) {
+        // Make sure the default profile isn't lost
+        self.profile_name_mut();
+        self.profiles
+            .push(format!("{DEFAULT_PROFILE} {}", self.profiles.len() + 1));
+        self.current_profile = self.profiles.len() - 1;
+    }
+
+    /// Switch to the next (or previous, for negative `offset`) profile
+    pub fn cycle_profile(&mut self, offset: isize) {
+        if self.profiles.is_empty() {
+            return;
+        }
+        self.current_profile = (self.current_profile as isize + offset)
+            .rem_euclid(self.profiles.len() as isize) as usize;
+    }
+
+    #[cfg(not(any(target_family = "wasm", test)))]
+    fn load() -> Self {
+        let Ok(contents) = std::fs::read_to_string(SAVE_PATH) else {
+            return Self::default();
+        };
+        ron::from_str(&contents).unwrap_or_else(|err| {
+            warn!("Failed to read save data, starting afresh: {err}");
+            Self::default()
+        })
+    }
+
+    #[cfg(any(target_family = "wasm", test))]
+    fn load() -> Self {
+        Self::default()
+    }
+
+    /// Write the save data to disk
+    pub fn save(&self) {
+        #[cfg(not(any(target_family = "wasm", test)))]
+        {
+            let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+                .map_err(|err| err.to_string())
+                .and_then(|contents| {
+                    std::fs::write(SAVE_PATH, contents).map_err(|err| err.to_string())
+                });
+            if let Err(err) = result {
+                error!("Failed to write save data: {err}");
+            }
+        }
+    }
+}
@@ -3,7 +3,10 @@
 use std::{borrow::Cow, time::Duration};
 
 use bevy::{
-    ecs::{spawn::SpawnWith, system::IntoObserverSystem},
+    ecs::{
+        spawn::{SpawnIter, SpawnWith},
+        system::IntoObserverSystem,
+    },
     prelude::*,
     ui::Val::*,
 };
@@ -86,6 +89,36 @@ pub fn label(text: impl Into<String>) -> impl Bundle {
     )
 }
 
+/// A row of three stars, with the first `count` filled in.
+pub fn stars(count: u8, size: f32) -> impl Bundle {
+    (
+        Name::new("Stars"),
+        Node {
+            column_gap: Px(size / 3.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Pickable::IGNORE,
+        Children::spawn(SpawnIter((0..3).map(move |i| {
+            (
+                Name::new("Star"),
+                Node {
+                    width: Px(size),
+                    height: Px(size),
+                    ..default()
+                },
+                BorderRadius::MAX,
+                BackgroundColor(if i < count {
+                    HEADER_TEXT
+                } else {
+                    HEADER_TEXT.with_alpha(0.2)
+                }),
+                Pickable::IGNORE,
+            )
+        }))),
+    )
+}
+
 /// A large rounded button with text and an action defined as an [`Observer`].
 pub fn button<E, B, M, I>(text: impl Into<String>, action: I) -> impl Bundle
 where